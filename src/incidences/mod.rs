@@ -1,22 +1,247 @@
-use std::collections::HashSet;
+use std::collections::{BTreeSet, HashSet};
 
+use super::nav::errors::{NavigatorError, Result};
 use super::nav::faceted_navigation::{fs, FacetedNavigation};
+use super::nav::formats::csv;
+use super::nav::ordering::Order;
+use super::nav::Essential;
 use clingo::Symbol;
 
 /// TODO
-pub fn show<T>(structure: &mut impl Incidences<T>, nav: &mut T) {
+#[allow(unused)]
+pub(crate) fn show<T>(structure: &mut impl Incidences<T>, nav: &mut T) {
     structure.show(nav)
 }
 
+/// Returns facet-by-answer-set incidence matrix under current route, facets in [`Order::Symbol`]
+/// and answer sets in solver order.
+#[allow(unused)]
+pub fn matrix<N: Essential + FacetedNavigation>(nav: &mut N) -> Result<Matrix> {
+    let Some(facets) = nav.sorted_facets(std::iter::empty::<String>(), Order::Symbol) else {
+        nav.expose().check_stopped()?;
+        return Err(NavigatorError::None);
+    };
+    let answer_sets = nav
+        .answer_sets(0, std::iter::empty::<String>())?
+        .into_iter()
+        .map(|xs| xs.into_iter().collect::<HashSet<_>>())
+        .collect::<Vec<_>>();
+
+    Ok(Matrix {
+        rows: facets
+            .iter()
+            .map(|f| answer_sets.iter().map(|xs| xs.contains(f)).collect())
+            .collect(),
+        facets: facets.iter().map(|f| f.to_string()).collect(),
+        answer_sets: answer_sets.len(),
+    })
+}
+
+/// Returns formal concepts of answer sets and facets under current route, computing at most `max`
+/// concepts.
+#[allow(unused)]
+pub fn lattice<N: Essential + FacetedNavigation>(nav: &mut N, max: usize) -> Result<Lattice> {
+    matrix(nav).map(|m| m.concepts(max))
+}
+
+/// Formal concept, i.e., a maximal set of answer sets sharing a maximal set of facets.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Concept {
+    /// Answer sets, as columns of incidence matrix.
+    pub extent: Vec<usize>,
+    /// Facets contained in all answer sets of extent.
+    pub intent: Vec<String>,
+}
+
+/// Concept lattice, concepts ordered by extent size.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Lattice {
+    pub concepts: Vec<Concept>,
+    /// Hasse diagram, where `(i, j)` means concept `i` is a direct subconcept of concept `j`.
+    pub edges: Vec<(usize, usize)>,
+    /// Whether all concepts were computed before reaching the maximum.
+    pub complete: bool,
+}
+
+/// Layout of exported matrices.
+#[allow(unused)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Layout {
+    /// CSV with one row per facet and one column per answer set or facet.
+    Dense,
+    /// CSV with one row per non-zero entry, i.e., coordinate format.
+    Sparse,
+}
+
+/// Incidences of facets and answer sets.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Matrix {
+    /// Facet labels, labelling rows.
+    pub facets: Vec<String>,
+    /// Number of answer sets, i.e., columns.
+    pub answer_sets: usize,
+    /// Whether answer set contains facet, by facet.
+    pub rows: Vec<Vec<bool>>,
+}
+impl Matrix {
+    /// Returns number of answer sets containing both facets, by pair of facets.
+    #[allow(unused)]
+    pub fn co_occurrences(&self) -> Vec<Vec<usize>> {
+        self.rows
+            .iter()
+            .map(|r| {
+                self.rows
+                    .iter()
+                    .map(|s| r.iter().zip(s).filter(|(x, y)| **x && **y).count())
+                    .collect()
+            })
+            .collect()
+    }
+
+    /// Returns at most `max` formal concepts and their Hasse diagram.
+    ///
+    /// Intents are generated as intersections of the facet sets of answer sets, starting from
+    /// all facets.
+    #[allow(unused)]
+    pub fn concepts(&self, max: usize) -> Lattice {
+        let objects = (0..self.answer_sets)
+            .map(|j| {
+                (0..self.facets.len())
+                    .filter(|i| self.rows[*i][j])
+                    .collect::<BTreeSet<_>>()
+            })
+            .collect::<Vec<_>>();
+
+        let top = (0..self.facets.len()).collect::<BTreeSet<_>>();
+        let mut intents = BTreeSet::new();
+        let mut complete = max > 0;
+        if complete {
+            intents.insert(top.clone());
+        }
+        let mut queue = vec![top];
+        'closure: while let Some(intent) = queue.pop() {
+            for o in objects.iter() {
+                let next = intent.intersection(o).copied().collect::<BTreeSet<_>>();
+                if intents.contains(&next) {
+                    continue;
+                }
+                if intents.len() >= max {
+                    complete = false;
+                    break 'closure;
+                }
+                intents.insert(next.clone());
+                queue.push(next);
+            }
+        }
+
+        let mut concepts = intents
+            .into_iter()
+            .map(|intent| {
+                let extent = (0..self.answer_sets)
+                    .filter(|j| intent.is_subset(&objects[*j]))
+                    .collect::<BTreeSet<_>>();
+                (extent, intent)
+            })
+            .collect::<Vec<_>>();
+        concepts.sort_by(|(e, _), (f, _)| e.len().cmp(&f.len()).then_with(|| e.cmp(f)));
+
+        let below = |i: usize, j: usize| i != j && concepts[i].0.is_subset(&concepts[j].0);
+        let mut edges = vec![];
+        for i in 0..concepts.len() {
+            for j in 0..concepts.len() {
+                if below(i, j) && !(0..concepts.len()).any(|k| below(i, k) && below(k, j)) {
+                    edges.push((i, j));
+                }
+            }
+        }
+
+        Lattice {
+            concepts: concepts
+                .into_iter()
+                .map(|(extent, intent)| Concept {
+                    extent: extent.into_iter().collect(),
+                    intent: intent.into_iter().map(|i| self.facets[i].clone()).collect(),
+                })
+                .collect(),
+            edges,
+            complete,
+        }
+    }
+
+    /// Exports facet-by-answer-set incidences, answer sets numbered from 1.
+    #[allow(unused)]
+    pub fn to_csv(&self, layout: Layout) -> String {
+        let rows = self
+            .rows
+            .iter()
+            .map(|r| r.iter().map(|x| *x as usize).collect::<Vec<_>>())
+            .collect::<Vec<_>>();
+        let columns = (1..=self.answer_sets)
+            .map(|i| i.to_string())
+            .collect::<Vec<_>>();
+
+        export(layout, "answer_set", &self.facets, &columns, &rows)
+    }
+
+    /// Exports facet-by-facet co-occurrences.
+    #[allow(unused)]
+    pub fn co_occurrences_to_csv(&self, layout: Layout) -> String {
+        export(
+            layout,
+            "other",
+            &self.facets,
+            &self.facets,
+            &self.co_occurrences(),
+        )
+    }
+}
+
+/// Exports `rows` labelled by `labels` with `columns`, whose header is `kind` in sparse layout.
+fn export(
+    layout: Layout,
+    kind: &str,
+    labels: &[String],
+    columns: &[String],
+    rows: &[Vec<usize>],
+) -> String {
+    match layout {
+        Layout::Dense => {
+            let header = std::iter::once("facet")
+                .chain(columns.iter().map(|c| c.as_str()))
+                .collect::<Vec<_>>();
+            csv(
+                &header,
+                labels.iter().zip(rows).map(|(l, r)| {
+                    std::iter::once(l.clone())
+                        .chain(r.iter().map(|x| x.to_string()))
+                        .collect()
+                }),
+            )
+        }
+        Layout::Sparse => csv(
+            &["facet", kind, "value"],
+            labels.iter().zip(rows).flat_map(|(l, r)| {
+                r.iter()
+                    .zip(columns)
+                    .filter(|(x, _)| **x > 0)
+                    .map(|(x, c)| vec![l.clone(), c.clone(), x.to_string()])
+            }),
+        ),
+    }
+}
+
+#[allow(unused)]
 #[derive(Debug, Clone)]
-pub enum Structure {
+pub(crate) enum Structure {
+    /// Facet incidences in given order, or in `Order::Symbol` if empty.
     F(Vec<Symbol>),
     B(Vec<Symbol>),
     C(Vec<Symbol>),
 }
 
-pub trait Incidences<T> {
+pub(crate) trait Incidences<T> {
     fn show(&mut self, nav: &mut T);
+    #[allow(unused)]
     fn ret<S: ToString>(
         &mut self,
         nav: &mut T,
@@ -30,21 +255,18 @@ impl<T: FacetedNavigation> Incidences<T> for Structure {
         match self {
             Self::F(ord) => {
                 let xs = match ord.is_empty() {
-                    true => fs(nav, (std::iter::empty::<String>(), std::iter::empty()))
-                        .unwrap_or(HashSet::new())
-                        .into_iter()
-                        .collect::<Vec<_>>(),
+                    true => Order::Symbol
+                        .sorted(fs(nav, std::iter::empty::<String>()).unwrap_or_default()),
                     _ => ord.to_vec(),
                 };
                 xs.iter()
-                    .map(|f| fs(nav, ([f].iter(), std::iter::empty())))
-                    .flatten()
+                    .filter_map(|f| fs(nav, [f.to_string()].iter()))
                     .for_each(|fs| {
                         xs.iter().for_each(|f| match fs.contains(f) {
                             true => print!("x"),
                             _ => print!(" "),
                         });
-                        print!("\n")
+                        println!()
                     });
             }
             _ => todo!(),
@@ -59,3 +281,51 @@ impl<T: FacetedNavigation> Incidences<T> for Structure {
         todo!()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::nav::limits::{within, Limit};
+    use crate::nav::{Navigation, Navigator};
+    use std::time::Duration;
+
+    #[test]
+    fn matrix_health() -> Result<()> {
+        let nav = Navigator::new("a;b. c;d :- b. e.", vec!["0".to_string()])?;
+        let mut anav = Navigation::And(nav);
+        anav.delta(["&", "b"].iter());
+        let m = matrix(&mut anav)?;
+        assert_eq!(m.facets, vec!["c".to_owned(), "d".to_owned()]);
+        assert_eq!(m.co_occurrences(), vec![vec![1, 0], vec![0, 1]]);
+        assert_eq!(m.to_csv(Layout::Dense).lines().count(), 3);
+        assert_eq!(
+            m.co_occurrences_to_csv(Layout::Sparse),
+            "facet,other,value\nc,c,1\nd,d,1"
+        );
+
+        // stopped solve calls are reported as such rather than as missing facets
+        let mut stopped = None;
+        let _ = within(&mut anav, Limit::timeout(Duration::ZERO), |nav| {
+            stopped = matrix(nav).err()
+        });
+        assert!(matches!(stopped, Some(NavigatorError::Timeout(_))));
+
+        Ok(())
+    }
+
+    #[test]
+    fn lattice_health() -> Result<()> {
+        let nav = Navigator::new("a;b. c;d :- b. e.", vec!["0".to_string()])?;
+        let mut anav = Navigation::And(nav);
+        let l = lattice(&mut anav, 16)?;
+        // bottom {a,b,c,d}, atoms {a}, {b,c}, {b,d}, join {b}, top {}
+        assert!(l.complete);
+        assert_eq!(l.concepts.len(), 6);
+        assert_eq!(l.concepts[0].intent.len(), 4);
+        assert!(l.concepts[5].intent.is_empty());
+        assert_eq!(l.edges.len(), 7);
+        assert!(!lattice(&mut anav, 3)?.complete);
+
+        Ok(())
+    }
+}
@@ -4,7 +4,7 @@ pub mod lex;
 pub mod nav;
 #[cfg(feature = "lnn")]
 pub mod lofo;
-//pub mod incidences;
+pub mod incidences;
 
 /// Parses facet.
 pub fn parse_facet(exp: &str) -> Option<clingo::Symbol> {
@@ -0,0 +1,196 @@
+use super::errors::{NavigatorError, Result};
+use super::faceted_navigation::FacetedNavigation;
+use super::modes::{Guide, Mode, Random};
+use super::{answer_set_count, Essential, Kind};
+use rand::{Rng, SeedableRng};
+use rand_chacha::ChaCha8Rng;
+
+use std::collections::BTreeMap;
+use std::time::{Duration, Instant};
+
+/// Activates facets picked by `mode` until current route determines a single answer set.
+///
+/// Navigation stops early once `mode` finds no facet to activate, e.g., since a goal turned
+/// unreachable. Facets are activated conjunctively, so disjunctive navigation and routes with
+/// disjuncts are rejected.
+#[allow(unused)]
+pub fn navigate<N: Essential + FacetedNavigation>(
+    nav: &mut N,
+    mode: &mut impl Guide,
+) -> Result<Trace> {
+    if nav.kind() == Kind::Or || !nav.expose().disjuncts.is_empty() {
+        return Err(NavigatorError::InvalidInput(nav.context()));
+    }
+    let start = Instant::now();
+    let (facets, answer_sets) = status(nav)?;
+    let mut trace = Trace {
+        facets,
+        answer_sets,
+        steps: vec![],
+        elapsed: Duration::ZERO,
+    };
+
+    let mut split_on = None;
+    while trace.remaining() > 0 {
+        let step_start = Instant::now();
+        let Some(((facet, _), weight)) = mode.step_scored(nav.expose(), &mut split_on) else {
+            nav.expose().check_stopped()?;
+            break;
+        };
+        nav.delta(["&", facet.as_str()].iter());
+        nav.update()?;
+
+        let (facets, answer_sets) = status(nav)?;
+        #[cfg(feature = "verbose")]
+        eprintln!("activated {facet}: {facets} facets, {answer_sets} answer sets");
+        trace.steps.push(Step {
+            facet,
+            weight,
+            facets,
+            answer_sets,
+            elapsed: step_start.elapsed(),
+        });
+    }
+    trace.elapsed = start.elapsed();
+
+    Ok(trace)
+}
+
+/// Runs `walks` random walks from current route down to a single answer set, restoring current
+/// route after each walk.
+///
+/// Walks are reproducible for a fixed `seed` on any platform, as the generator, unlike `StdRng`,
+/// does not change with `rand` releases.
+#[allow(unused)]
+pub fn simulate<N: Essential + FacetedNavigation>(
+    nav: &mut N,
+    walks: usize,
+    seed: u64,
+) -> Result<Walks> {
+    let inner = nav.expose();
+    let saved = (
+        inner.conjuncts.clone(),
+        inner.disjuncts.clone(),
+        inner.route.clone(),
+    );
+    let mut rng = ChaCha8Rng::seed_from_u64(seed);
+
+    let mut lengths = vec![];
+    let mut incomplete = 0;
+    for _ in 0..walks {
+        let trace = navigate(nav, &mut Mode::Random(Random::new(rng.gen())));
+
+        let inner = nav.expose();
+        inner.conjuncts = saved.0.clone();
+        inner.disjuncts = saved.1.clone();
+        inner.route = saved.2.clone();
+        nav.update()?;
+
+        let trace = trace?;
+        match trace.is_complete() {
+            true => lengths.push(trace.steps.len()),
+            _ => incomplete += 1,
+        }
+    }
+
+    Ok(Walks {
+        lengths,
+        incomplete,
+    })
+}
+
+/// Path lengths of random walks run by [`simulate`].
+#[allow(unused)]
+#[derive(Debug, Clone)]
+pub struct Walks {
+    /// Number of activated facets of each walk reaching a single answer set.
+    pub lengths: Vec<usize>,
+    /// Number of walks stopped before reaching a single answer set.
+    pub incomplete: usize,
+}
+impl Walks {
+    /// Returns number of walks per path length.
+    #[allow(unused)]
+    pub fn distribution(&self) -> BTreeMap<usize, usize> {
+        let mut distribution = BTreeMap::new();
+        for l in self.lengths.iter() {
+            *distribution.entry(*l).or_insert(0) += 1;
+        }
+        distribution
+    }
+
+    #[allow(unused)]
+    pub fn min(&self) -> Option<usize> {
+        self.lengths.iter().min().copied()
+    }
+
+    #[allow(unused)]
+    pub fn max(&self) -> Option<usize> {
+        self.lengths.iter().max().copied()
+    }
+
+    #[allow(unused)]
+    pub fn mean(&self) -> Option<f64> {
+        match self.lengths.len() {
+            0 => None,
+            n => Some(self.lengths.iter().sum::<usize>() as f64 / n as f64),
+        }
+    }
+}
+
+/// Steps taken by [`navigate`].
+#[derive(Debug, Clone)]
+pub struct Trace {
+    /// Facet count before first step.
+    pub facets: usize,
+    /// Answer set count before first step.
+    pub answer_sets: usize,
+    pub steps: Vec<Step>,
+    /// Total elapsed time.
+    pub elapsed: Duration,
+}
+impl Trace {
+    /// Returns facet count after last step.
+    #[allow(unused)]
+    pub fn remaining(&self) -> usize {
+        self.steps.last().map_or(self.facets, |s| s.facets)
+    }
+
+    /// Returns whether route determines a single answer set after last step.
+    #[allow(unused)]
+    pub fn is_complete(&self) -> bool {
+        self.steps
+            .last()
+            .map_or(self.answer_sets, |s| s.answer_sets)
+            == 1
+    }
+}
+
+/// Activation of a single facet.
+#[allow(unused)]
+#[derive(Debug, Clone)]
+pub struct Step {
+    /// Activated facet, prefixed by `~` if negated.
+    pub facet: String,
+    /// Score of facet under mode that picked it.
+    pub weight: f64,
+    /// Facet count after activation.
+    pub facets: usize,
+    /// Answer set count after activation.
+    pub answer_sets: usize,
+    /// Time taken to pick and activate facet.
+    pub elapsed: Duration,
+}
+
+/// Returns facet and answer set count under current route.
+fn status<N: Essential + FacetedNavigation>(nav: &mut N) -> Result<(usize, usize)> {
+    let Some(facets) = nav.facets(std::iter::empty::<String>()) else {
+        nav.expose().check_stopped()?;
+        return Err(NavigatorError::None);
+    };
+    let facets = facets.len();
+    let route = nav.assumptions(std::iter::empty::<String>())?;
+    let answer_sets = answer_set_count(nav.expose(), &route, 0)?;
+
+    Ok((facets, answer_sets))
+}
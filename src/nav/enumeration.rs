@@ -0,0 +1,335 @@
+use super::errors::{NavigatorError, Partial, Result};
+use super::limits::{next_model, Next};
+use super::ordering::Order;
+use super::{answer_set_count, collect_answer_sets, parse, Essential, Navigator};
+use clingo::{SolverLiteral, Symbol};
+use rand::{Rng, SeedableRng};
+use rand_chacha::ChaCha8Rng;
+
+use std::cmp::Reverse;
+use std::collections::{BinaryHeap, HashSet};
+
+#[cfg(feature = "verbose")]
+use std::time::Instant;
+
+/// Returns `k` answer sets drawn uniformly at random and independently under current route
+/// conjunctively extended by `peek_on`, reproducible for a fixed `seed`.
+///
+/// Samples are drawn by reservoir sampling during a single enumeration, i.e., in time linear in
+/// the number of answer sets `n` plus `O(k log n)` expected replacements and space linear in `k`.
+#[allow(unused)]
+pub fn sample<S: ToString>(
+    nav: &mut impl Essential,
+    k: usize,
+    seed: u64,
+    peek_on: impl Iterator<Item = S>,
+) -> Result<Vec<Vec<Symbol>>> {
+    let route = nav.assumptions(peek_on)?;
+    let nav = nav.expose();
+
+    #[cfg(feature = "verbose")]
+    eprintln!("sampling started");
+    #[cfg(feature = "verbose")]
+    let start = Instant::now();
+    let samples = samples(nav, &route, k, seed);
+
+    #[cfg(feature = "verbose")]
+    eprintln!("sampling elapsed: {:?}", start.elapsed());
+    samples
+}
+
+/// Returns up to `k` pairwise distinct answer sets under current route conjunctively extended by
+/// `peek_on`, picked greedily such that each is as far as possible from those picked before.
+///
+/// Distance is the number of facets on which two answer sets differ. Answer sets are picked among
+/// all answer sets if there are at most `pool`, else among `pool` samples drawn with `seed`, where
+/// `pool` must be positive.
+#[allow(unused)]
+pub fn diverse<S: ToString>(
+    nav: &mut impl Essential,
+    k: usize,
+    pool: usize,
+    seed: u64,
+    peek_on: impl Iterator<Item = S>,
+) -> Result<Vec<Vec<Symbol>>> {
+    if pool == 0 {
+        return Err(NavigatorError::InvalidInput(pool.to_string()));
+    }
+    let route = nav.assumptions(peek_on)?;
+    let nav = nav.expose();
+
+    #[cfg(feature = "verbose")]
+    eprintln!("diverse enumeration started");
+    #[cfg(feature = "verbose")]
+    let start = Instant::now();
+    let candidates = match answer_set_count(nav, &route, pool)? <= pool {
+        true => collect_answer_sets(nav, &route, 0)?,
+        _ => samples(nav, &route, pool, seed)?,
+    }
+    .into_iter()
+    .map(|xs| xs.into_iter().collect::<HashSet<_>>())
+    .collect::<Vec<_>>();
+
+    // minimal distance of each candidate to answer sets picked so far
+    let mut distances = vec![usize::MAX; candidates.len()];
+    let mut picked: Vec<usize> = vec![];
+    while picked.len() < k {
+        let Some((i, _)) = distances
+            .iter()
+            .enumerate()
+            .filter(|(_, d)| **d > 0)
+            .rev()
+            .max_by_key(|(_, d)| **d)
+        else {
+            break;
+        };
+        picked.push(i);
+        for (d, xs) in distances.iter_mut().zip(candidates.iter()) {
+            *d = (*d).min(candidates[i].symmetric_difference(xs).count());
+        }
+    }
+
+    #[cfg(feature = "verbose")]
+    eprintln!("diverse enumeration elapsed: {:?}", start.elapsed());
+    Ok(picked
+        .into_iter()
+        .map(|i| Order::Symbol.sorted(candidates[i].iter().copied()))
+        .collect())
+}
+
+/// Returns up to `n` answer sets under current route violating as few facets in `wishes` as
+/// possible, or all such answer sets if `n` is 0.
+///
+/// Wishes are given as `f` or `~f` and act as soft assumptions, i.e., the number of violated
+/// wishes is minimized on a copy of the program encoding the route by integrity constraints, at a
+/// priority above all optimization statements of the program. Programs whose priorities are no
+/// integers are rejected. Priorities are read from the program text, so optimization statements in
+/// files included by `#include` may take precedence over wishes.
+#[allow(unused)]
+pub fn closest<S: ToString>(
+    nav: &mut impl Essential,
+    wishes: impl Iterator<Item = S>,
+    n: usize,
+) -> Result<Vec<Closest>> {
+    let constraints = nav.constraints();
+    let nav = nav.expose();
+    nav.check_stopped()?;
+    let wishes = wishes
+        .map(|w| {
+            let w = w.to_string();
+            let (symbol, exc) = match w.strip_prefix('~') {
+                Some(symbol) => (symbol, true),
+                _ => (w.as_str(), false),
+            };
+            match parse(symbol) {
+                Some(sym) => Ok((w.clone(), sym, exc)),
+                _ => Err(NavigatorError::InvalidInput(w.clone())),
+            }
+        })
+        .collect::<Result<Vec<_>>>()?;
+
+    let p = priority(&nav.input.0)?;
+    let minimize = wishes
+        .iter()
+        .enumerate()
+        .map(|(i, (_, sym, exc))| match exc {
+            true => format!("#minimize {{ 1@{p},{i} : {sym} }}."),
+            _ => format!("#minimize {{ 1@{p},{i} : not {sym} }}."),
+        })
+        .collect::<Vec<_>>()
+        .join("\n");
+    let lp = format!("{}\n{constraints}\n{minimize}", nav.input.0);
+    let mut args = nav.input.1.clone();
+    args.extend(["--opt-mode=optN".to_owned(), "0".to_owned()]);
+
+    #[cfg(feature = "verbose")]
+    eprintln!("closest started");
+    #[cfg(feature = "verbose")]
+    let start = Instant::now();
+    let mut ctl = clingo::control(args)?;
+    ctl.add("base", &[], &lp)?;
+    ctl.ground(&[clingo::Part::new("base", vec![])?])?;
+
+    let (mode, limit) = (nav.solve_mode(), nav.limit.clone());
+    let mut handle = ctl.fasb_solve(mode, &[])?;
+    let mut closest = vec![];
+    let mut stopped = None;
+
+    loop {
+        let model = match next_model(&mut handle, &limit)? {
+            Next::Model(model) => model,
+            Next::Exhausted => break,
+            Next::Stopped(interruption) => {
+                stopped = Some(interruption);
+                break;
+            }
+        };
+        if model.optimality_proven()? {
+            let xs = model.symbols(clingo::ShowType::SHOWN)?;
+            let contained = xs.iter().collect::<HashSet<_>>();
+            closest.push(Closest {
+                dropped: wishes
+                    .iter()
+                    .filter(|(_, sym, exc)| contained.contains(sym) == *exc)
+                    .map(|(w, _, _)| w.clone())
+                    .collect(),
+                answer_set: xs,
+            });
+            if n > 0 && closest.len() >= n {
+                break;
+            }
+        }
+        handle.resume()?;
+    }
+
+    handle.close()?;
+    #[cfg(feature = "verbose")]
+    eprintln!("closest elapsed: {:?}", start.elapsed());
+
+    match stopped {
+        Some(interruption) => Err(nav.stop(
+            interruption,
+            Partial::AnswerSets(closest.into_iter().map(|c| c.answer_set).collect()),
+        )),
+        _ => Ok(closest),
+    }
+}
+
+/// Answer set closest to a wish-list.
+#[allow(unused)]
+#[derive(Debug, Clone)]
+pub struct Closest {
+    pub answer_set: Vec<Symbol>,
+    /// Wishes violated by answer set.
+    pub dropped: Vec<String>,
+}
+
+/// Returns priority above all priorities in `lp`, i.e., integers following `@` outside of
+/// comments, strings, `#script` blocks and external function calls.
+///
+/// Files included by `#include` are not read, so priorities in them are not taken into account.
+pub(super) fn priority(lp: &str) -> Result<i64> {
+    let lp = uncommented(lp);
+    let mut max = 0;
+    for (i, _) in lp.match_indices('@') {
+        let rest = lp[i + 1..].trim_start();
+        let end = rest
+            .char_indices()
+            .find(|(j, c)| !(c.is_ascii_digit() || (*j == 0 && *c == '-')))
+            .map_or(rest.len(), |(j, _)| j);
+        let name = rest
+            .find(|c: char| !(c.is_alphanumeric() || c == '_'))
+            .unwrap_or(rest.len());
+        match rest[..end].parse::<i64>() {
+            Ok(p) => max = max.max(p),
+            _ if rest.starts_with(|c: char| c.is_ascii_lowercase())
+                && rest[name..].starts_with('(') => {}
+            _ => return Err(NavigatorError::InvalidInput(format!("@{}", &rest[..name]))),
+        }
+    }
+
+    Ok(max + 1)
+}
+
+/// Returns `lp` without line and block comments and `#script` blocks, and with empty strings in
+/// place of strings.
+fn uncommented(lp: &str) -> String {
+    let mut uncommented = String::with_capacity(lp.len());
+    let mut rest = lp;
+    while let Some(i) = rest.find(['%', '"', '#']) {
+        uncommented.push_str(&rest[..i]);
+        let tail = &rest[i + 1..];
+        rest = match &rest[i..i + 1] {
+            "%" => match tail.strip_prefix('*') {
+                Some(block) => block.find("*%").map_or("", |j| &block[j + 2..]),
+                _ => tail.find('\n').map_or("", |j| &tail[j..]),
+            },
+            "\"" => {
+                uncommented.push_str("\"\"");
+                let mut escaped = false;
+                let end = tail.char_indices().find(|(_, c)| {
+                    let end = !escaped && *c == '"';
+                    escaped = !escaped && *c == '\\';
+                    end
+                });
+                end.map_or("", |(j, _)| &tail[j + 1..])
+            }
+            _ => match tail.strip_prefix("script") {
+                Some(script) => script.find("#end.").map_or("", |j| &script[j + 5..]),
+                _ => {
+                    uncommented.push('#');
+                    tail
+                }
+            },
+        };
+    }
+    uncommented.push_str(rest);
+    uncommented
+}
+
+/// Returns `k` answer sets drawn uniformly at random under `route`, replacing each sample by the
+/// `i`-th answer set enumerated with probability `1/i`.
+///
+/// Instead of a draw per sample and answer set, the index of the next answer set replacing a
+/// sample is drawn whenever it is replaced, which happens `O(log n)` times in expectation for `n`
+/// answer sets.
+fn samples(
+    nav: &mut Navigator,
+    route: &[SolverLiteral],
+    k: usize,
+    seed: u64,
+) -> Result<Vec<Vec<Symbol>>> {
+    nav.check_stopped()?;
+    if k == 0 {
+        return Ok(vec![]);
+    }
+    let mut rng = ChaCha8Rng::seed_from_u64(seed);
+    let (mode, limit) = (nav.solve_mode(), nav.limit.clone());
+    let mut handle = nav.ctl.fasb_solve(mode, route)?;
+    let mut samples: Vec<Vec<Symbol>> = vec![vec![]; k];
+    // index of answer set replacing sample next, which is the first one for every sample
+    let mut next = (0..k).map(|j| Reverse((1, j))).collect::<BinaryHeap<_>>();
+    let mut i = 0;
+    let mut stopped = None;
+
+    loop {
+        let answer_set = match next_model(&mut handle, &limit)? {
+            Next::Model(model) => model,
+            Next::Exhausted => break,
+            Next::Stopped(interruption) => {
+                stopped = Some(interruption);
+                break;
+            }
+        };
+        i += 1;
+        if next.peek().is_some_and(|Reverse((m, _))| *m == i) {
+            let xs = answer_set.symbols(clingo::ShowType::SHOWN)?;
+            while let Some(Reverse((m, j))) = next.peek().copied() {
+                if m != i {
+                    break;
+                }
+                next.pop();
+                samples[j] = xs.clone();
+                next.push(Reverse((skip(&mut rng, i), j)));
+            }
+        }
+        handle.resume()?;
+    }
+    if i == 0 {
+        samples.clear();
+    }
+
+    handle.close()?;
+
+    match stopped {
+        Some(interruption) => Err(nav.stop(interruption, Partial::AnswerSets(samples))),
+        _ => Ok(samples),
+    }
+}
+
+/// Returns index of answer set next replacing a sample last replaced by the `i`-th one, i.e., `m`
+/// with probability `i/(m-1) - i/m`.
+fn skip(rng: &mut impl Rng, i: usize) -> usize {
+    let u = 1.0 - rng.gen::<f64>();
+    (i as f64 / u).floor() as usize + 1
+}
@@ -1,3 +1,4 @@
+use clingo::Symbol;
 use thiserror::Error;
 
 #[derive(Error, Debug)]
@@ -10,6 +11,24 @@ pub enum NavigatorError {
     IOError(#[from] std::io::Error),
     #[error("Invalid input.")]
     InvalidInput(String),
+    #[error("Timeout.")]
+    Timeout(Partial),
+    #[error("Interrupted.")]
+    Interrupted(Partial),
+}
+
+/// Result computed before an operation was stopped.
+#[allow(unused)]
+#[derive(Debug, Clone)]
+pub enum Partial {
+    /// No meaningful partial result.
+    None,
+    /// Lower bound on answer set count.
+    Count(usize),
+    /// Consequences of last model found.
+    Consequences(Vec<Symbol>),
+    /// Answer sets enumerated so far.
+    AnswerSets(Vec<Vec<Symbol>>),
 }
 
 pub type Result<T> = std::result::Result<T, NavigatorError>;
@@ -1,3 +1,6 @@
+use super::errors::Partial;
+use super::limits::{next_model, Next};
+use super::ordering::Order;
 use super::utils::ToHashSet;
 use super::{parse, Navigation};
 use clingo::{SolverLiteral, Symbol};
@@ -69,7 +72,7 @@ fn nav_route<S: ToString>(
 
             (nav, route)
         }
-        Navigation::AndOr(nav) => {
+        Navigation::AndOr(nav) | Navigation::Or(nav) => {
             let route = peek_on
                 .map(|f| {
                     let s = f.to_string();
@@ -91,6 +94,7 @@ fn nav_route<S: ToString>(
     }
 }
 
+/// Consequences are returned in [`Order::Symbol`], whereas facets come as a set.
 pub trait FacetedNavigation {
     fn brave_consequences<S: ToString>(
         &mut self,
@@ -101,6 +105,12 @@ pub trait FacetedNavigation {
         peek_on: impl Iterator<Item = S>,
     ) -> Option<Vec<Symbol>>;
     fn facets<S: ToString>(&mut self, peek_on: impl Iterator<Item = S>) -> Option<HashSet<Symbol>>;
+    /// Returns facets under current route conjunctively extended by `peek_on` in `order`.
+    fn sorted_facets<S: ToString>(
+        &mut self,
+        peek_on: impl Iterator<Item = S>,
+        order: Order,
+    ) -> Option<Vec<Symbol>>;
     fn stats<S: ToString>(
         &mut self,
         peek_on: impl Iterator<Item = S>,
@@ -138,6 +148,14 @@ impl FacetedNavigation for Navigation {
         }
     }
 
+    fn sorted_facets<S: ToString>(
+        &mut self,
+        peek_on: impl Iterator<Item = S>,
+        order: Order,
+    ) -> Option<Vec<Symbol>> {
+        self.facets(peek_on).map(|fs| order.sorted(fs))
+    }
+
     fn stats<S: ToString>(
         &mut self,
         peek_on: impl Iterator<Item = S>,
@@ -185,6 +203,7 @@ impl BCCC for Consequences {
         eprintln!("{s} started");
         #[cfg(feature = "ultra_verbose")]
         let start = Instant::now();
+        nav.check_stopped().ok()?;
         nav.ctl
             .configuration_mut()
             .map(|c| {
@@ -196,9 +215,19 @@ impl BCCC for Consequences {
             .ok()?;
 
         let mut xs = vec![];
-        let mut handle = nav.ctl.fasb_solve(clingo::SolveMode::YIELD, &route).ok()?;
+        let (mode, limit) = (nav.solve_mode(), nav.limit.clone());
+        let mut handle = nav.ctl.fasb_solve(mode, &route).ok()?;
+        let mut stopped = None;
 
-        while let Ok(Some(ys)) = handle.model() {
+        loop {
+            let ys = match next_model(&mut handle, &limit).ok()? {
+                Next::Model(model) => model,
+                Next::Exhausted => break,
+                Next::Stopped(interruption) => {
+                    stopped = Some(interruption);
+                    break;
+                }
+            };
             xs = ys.symbols(clingo::ShowType::SHOWN).ok()?;
             handle.resume().ok()?;
         }
@@ -216,6 +245,12 @@ impl BCCC for Consequences {
         #[cfg(feature = "ultra_verbose")]
         eprintln!("{s} elapsed: {:?}", start.elapsed());
 
+        if let Some(interruption) = stopped {
+            nav.stop(interruption, Partial::Consequences(xs));
+            return None;
+        }
+
+        Order::Symbol.sort(&mut xs);
         Some(xs)
     }
 }
@@ -0,0 +1,263 @@
+use super::errors::Result;
+use super::faceted_navigation::FacetedNavigation;
+use super::ordering::Order;
+use super::Essential;
+use clingo::Symbol;
+use serde_json::{json, Value};
+
+/// Formats `n` answer sets under current route conjunctively extended by `peek_on`.
+#[allow(unused)]
+pub fn answer_sets<S: ToString>(
+    nav: &mut impl Essential,
+    n: usize,
+    peek_on: impl Iterator<Item = S>,
+    format: &impl Formatter,
+) -> Result<String> {
+    let mut xs = nav.answer_sets(if n == 0 { 0 } else { n + 1 }, peek_on)?;
+    let more = n > 0 && xs.len() > n;
+    xs.truncate(if n == 0 { xs.len() } else { n });
+
+    Ok(format.answer_sets(&xs, more))
+}
+
+/// Formats brave consequences under current route conjunctively extended by `peek_on`.
+#[allow(unused)]
+pub fn brave_consequences<S: ToString>(
+    nav: &mut impl FacetedNavigation,
+    peek_on: impl Iterator<Item = S>,
+    format: &impl Formatter,
+) -> Option<String> {
+    nav.brave_consequences(peek_on)
+        .map(|xs| format.consequences("brave", &xs))
+}
+
+/// Formats cautious consequences under current route conjunctively extended by `peek_on`.
+#[allow(unused)]
+pub fn cautious_consequences<S: ToString>(
+    nav: &mut impl FacetedNavigation,
+    peek_on: impl Iterator<Item = S>,
+    format: &impl Formatter,
+) -> Option<String> {
+    nav.cautious_consequences(peek_on)
+        .map(|xs| format.consequences("cautious", &xs))
+}
+
+/// Formats facets under current route conjunctively extended by `peek_on` in [`Order::Symbol`].
+#[allow(unused)]
+pub fn facets<S: ToString>(
+    nav: &mut impl FacetedNavigation,
+    peek_on: impl Iterator<Item = S>,
+    format: &impl Formatter,
+) -> Option<String> {
+    nav.sorted_facets(peek_on, Order::Symbol)
+        .map(|fs| format.facets(&fs))
+}
+
+/// Formats brave consequence, cautious consequence and facet counts under current route
+/// conjunctively extended by `peek_on`.
+#[allow(unused)]
+pub fn stats<S: ToString>(
+    nav: &mut impl FacetedNavigation,
+    peek_on: impl Iterator<Item = S>,
+    format: &impl Formatter,
+) -> Option<String> {
+    nav.stats(peek_on).map(|stats| format.stats(stats))
+}
+
+/// Formats route.
+#[allow(unused)]
+pub fn context(nav: &impl Essential, format: &impl Formatter) -> String {
+    format.context(&nav.context())
+}
+
+#[allow(unused)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Format {
+    /// JSON as emitted by clingo with `--outf=2`.
+    Clingo,
+    /// One JSON object per line.
+    JsonLines,
+    /// Comma separated values with header row.
+    Csv,
+}
+
+pub trait Formatter {
+    /// Formats `answer_sets`, where `more` signals that further answer sets exist.
+    fn answer_sets(&self, answer_sets: &[Vec<Symbol>], more: bool) -> String;
+    /// Formats consequences of `kind` (`brave` or `cautious`).
+    fn consequences(&self, kind: &str, consequences: &[Symbol]) -> String;
+    /// Formats facets.
+    fn facets(&self, facets: &[Symbol]) -> String;
+    /// Formats brave consequence, cautious consequence and facet counts.
+    fn stats(&self, stats: (usize, usize, usize)) -> String;
+    /// Formats route.
+    fn context(&self, route: &str) -> String;
+}
+impl Formatter for Format {
+    fn answer_sets(&self, answer_sets: &[Vec<Symbol>], more: bool) -> String {
+        match self {
+            Self::Clingo => clingo_json(
+                answer_sets.iter().map(|xs| values(xs)).collect(),
+                json!({
+                    "Number": answer_sets.len(),
+                    "More": yes_no(more),
+                }),
+            ),
+            Self::JsonLines => answer_sets
+                .iter()
+                .enumerate()
+                .map(|(i, xs)| {
+                    json!({
+                        "type": "answer_set",
+                        "index": i + 1,
+                        "atoms": values(xs),
+                    })
+                    .to_string()
+                })
+                .collect::<Vec<_>>()
+                .join("\n"),
+            Self::Csv => csv(
+                &["answer_set", "atom"],
+                answer_sets.iter().enumerate().flat_map(|(i, xs)| {
+                    xs.iter()
+                        .map(move |x| vec![(i + 1).to_string(), x.to_string()])
+                }),
+            ),
+        }
+    }
+
+    fn consequences(&self, kind: &str, consequences: &[Symbol]) -> String {
+        match self {
+            Self::Clingo => {
+                let mut models = json!({
+                    "Number": 1,
+                    "More": "no",
+                });
+                models[capitalize(kind)] = json!("yes");
+                clingo_json(vec![values(consequences)], models)
+            }
+            Self::JsonLines => consequences
+                .iter()
+                .map(|x| json!({ "type": kind, "atom": x.to_string() }).to_string())
+                .collect::<Vec<_>>()
+                .join("\n"),
+            Self::Csv => csv(
+                &["kind", "atom"],
+                consequences
+                    .iter()
+                    .map(|x| vec![kind.to_owned(), x.to_string()]),
+            ),
+        }
+    }
+
+    fn facets(&self, facets: &[Symbol]) -> String {
+        match self {
+            Self::Clingo => json!({
+                "Solver": SOLVER,
+                "Facets": values(facets),
+                "Number": facets.len(),
+            })
+            .to_string(),
+            Self::JsonLines => facets
+                .iter()
+                .map(|f| json!({ "type": "facet", "atom": f.to_string() }).to_string())
+                .collect::<Vec<_>>()
+                .join("\n"),
+            Self::Csv => csv(&["facet"], facets.iter().map(|f| vec![f.to_string()])),
+        }
+    }
+
+    fn stats(&self, (bcc, ccc, fc): (usize, usize, usize)) -> String {
+        match self {
+            Self::Clingo => json!({
+                "Solver": SOLVER,
+                "Stats": {
+                    "Brave": bcc,
+                    "Cautious": ccc,
+                    "Facets": fc,
+                },
+            })
+            .to_string(),
+            Self::JsonLines => json!({
+                "type": "stats",
+                "brave": bcc,
+                "cautious": ccc,
+                "facets": fc,
+            })
+            .to_string(),
+            Self::Csv => csv(
+                &["brave", "cautious", "facets"],
+                std::iter::once(vec![bcc.to_string(), ccc.to_string(), fc.to_string()]),
+            ),
+        }
+    }
+
+    fn context(&self, route: &str) -> String {
+        let route = route.trim();
+        match self {
+            Self::Clingo => json!({ "Solver": SOLVER, "Route": route }).to_string(),
+            Self::JsonLines => json!({ "type": "route", "route": route }).to_string(),
+            Self::Csv => csv(&["route"], std::iter::once(vec![route.to_owned()])),
+        }
+    }
+}
+
+const SOLVER: &str = concat!("minifasb version ", env!("CARGO_PKG_VERSION"));
+
+fn clingo_json(witnesses: Vec<Value>, models: Value) -> String {
+    let result = match witnesses.is_empty() {
+        true => "UNSATISFIABLE",
+        _ => "SATISFIABLE",
+    };
+    json!({
+        "Solver": SOLVER,
+        "Call": [{
+            "Witnesses": witnesses
+                .into_iter()
+                .map(|xs| json!({ "Value": xs }))
+                .collect::<Vec<_>>(),
+        }],
+        "Result": result,
+        "Models": models,
+        "Calls": 1,
+    })
+    .to_string()
+}
+
+fn values(xs: &[Symbol]) -> Value {
+    Value::from(xs.iter().map(|x| x.to_string()).collect::<Vec<_>>())
+}
+
+fn yes_no(b: bool) -> &'static str {
+    match b {
+        true => "yes",
+        _ => "no",
+    }
+}
+
+fn capitalize(s: &str) -> String {
+    let mut cs = s.chars();
+    cs.next()
+        .map(|c| c.to_uppercase().chain(cs).collect())
+        .unwrap_or_default()
+}
+
+pub(crate) fn csv(header: &[&str], rows: impl Iterator<Item = Vec<String>>) -> String {
+    std::iter::once(header.join(","))
+        .chain(rows.map(|row| {
+            row.iter()
+                .map(|field| csv_field(field))
+                .collect::<Vec<_>>()
+                .join(",")
+        }))
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Quotes `field` if it contains a separator, quote or line break.
+fn csv_field(field: &str) -> String {
+    match field.contains([',', '"', '\n', '\r']) {
+        true => format!("\"{}\"", field.replace('"', "\"\"")),
+        _ => field.to_owned(),
+    }
+}
@@ -0,0 +1,122 @@
+use super::errors::{NavigatorError, Result};
+use super::faceted_navigation::FacetedNavigation;
+use super::ordering::Order;
+use super::Essential;
+
+use std::collections::HashSet;
+
+#[cfg(feature = "verbose")]
+use std::time::Instant;
+
+/// Returns implications between facets under current route, where `f` implies `g` if activating
+/// `f` makes `g` cautious, and `f` excludes `g` if activating `f` makes `~g` cautious.
+#[allow(unused)]
+pub fn implications<N: Essential + FacetedNavigation>(nav: &mut N) -> Result<Implications> {
+    let Some(fs) = nav.sorted_facets(std::iter::empty::<String>(), Order::Symbol) else {
+        nav.expose().check_stopped()?;
+        return Err(NavigatorError::None);
+    };
+    let fs = fs.iter().map(|f| f.to_string()).collect::<Vec<_>>();
+
+    #[cfg(feature = "verbose")]
+    eprintln!("implications started");
+    #[cfg(feature = "verbose")]
+    let start = Instant::now();
+    // facets implied and excluded by each facet
+    let mut relation = Vec::with_capacity(fs.len());
+    for f in fs.iter() {
+        let (Some(bc), Some(cc)) = (
+            nav.brave_consequences([f].iter()),
+            nav.cautious_consequences([f].iter()),
+        ) else {
+            nav.expose().check_stopped()?;
+            return Err(NavigatorError::None);
+        };
+        let bc = bc.iter().map(|x| x.to_string()).collect::<HashSet<_>>();
+        let cc = cc.iter().map(|x| x.to_string()).collect::<HashSet<_>>();
+        relation.push((
+            (0..fs.len())
+                .filter(|j| cc.contains(&fs[*j]))
+                .collect::<HashSet<_>>(),
+            (0..fs.len())
+                .filter(|j| !bc.contains(&fs[*j]))
+                .collect::<HashSet<_>>(),
+        ));
+    }
+
+    // facets implying each other share a class, represented by its first facet
+    let mut class = vec![None; fs.len()];
+    let mut classes = vec![];
+    let mut representatives = vec![];
+    for i in 0..fs.len() {
+        if class[i].is_some() {
+            continue;
+        }
+        let members = (i..fs.len())
+            .filter(|j| relation[i].0.contains(j) && relation[*j].0.contains(&i))
+            .collect::<Vec<_>>();
+        members.iter().for_each(|j| class[*j] = Some(classes.len()));
+        classes.push(members.iter().map(|j| fs[*j].clone()).collect::<Vec<_>>());
+        representatives.push(i);
+    }
+
+    let implies =
+        |c: usize, d: usize| c != d && relation[representatives[c]].0.contains(&representatives[d]);
+    let mut edges = vec![];
+    let mut excludes = vec![];
+    for c in 0..classes.len() {
+        for d in 0..classes.len() {
+            if implies(c, d) && !(0..classes.len()).any(|e| implies(c, e) && implies(e, d)) {
+                edges.push((c, d));
+            }
+            if c < d && relation[representatives[c]].1.contains(&representatives[d]) {
+                excludes.push((c, d));
+            }
+        }
+    }
+
+    #[cfg(feature = "verbose")]
+    eprintln!("implications elapsed: {:?}", start.elapsed());
+    Ok(Implications {
+        classes,
+        implies: edges,
+        excludes,
+    })
+}
+
+/// Implication graph between classes of equivalent facets.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Implications {
+    /// Classes of facets implying each other, in [`Order::Symbol`].
+    pub classes: Vec<Vec<String>>,
+    /// Transitively reduced implications, where `(c, d)` means facets of class `c` imply facets
+    /// of class `d`.
+    pub implies: Vec<(usize, usize)>,
+    /// Mutually exclusive classes `(c, d)`, where `c < d`.
+    pub excludes: Vec<(usize, usize)>,
+}
+impl Implications {
+    /// Returns graph in DOT, drawing exclusions as dashed undirected edges.
+    #[allow(unused)]
+    pub fn to_dot(&self) -> String {
+        let nodes = self.classes.iter().enumerate().map(|(c, fs)| {
+            format!(
+                "    {c} [label=\"{}\"];",
+                fs.join(", ").replace('\\', "\\\\").replace('"', "\\\"")
+            )
+        });
+        let implies = self.implies.iter().map(|(c, d)| format!("    {c} -> {d};"));
+        let excludes = self
+            .excludes
+            .iter()
+            .map(|(c, d)| format!("    {c} -> {d} [dir=none, style=dashed];"));
+
+        std::iter::once("digraph implications {".to_owned())
+            .chain(nodes)
+            .chain(implies)
+            .chain(excludes)
+            .chain(std::iter::once("}".to_owned()))
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+}
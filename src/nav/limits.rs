@@ -0,0 +1,175 @@
+use super::errors::{NavigatorError, Partial, Result};
+use super::Essential;
+use clingo::{Model, SolveHandle};
+
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+/// Interval in which a running solve call checks its limit.
+const POLL: Duration = Duration::from_millis(10);
+
+/// Runs `f` on `nav`, cancelling all solve calls made by `f` once `limit` is exceeded.
+///
+/// Returns [`NavigatorError::Timeout`] or [`NavigatorError::Interrupted`] carrying the partial
+/// result of the solve call that was cancelled, e.g., a lower bound on the answer set count.
+#[allow(unused)]
+pub fn within<N: Essential, T>(
+    nav: &mut N,
+    limit: Limit,
+    f: impl FnOnce(&mut N) -> T,
+) -> Result<T> {
+    let outer = nav.expose().limit.replace(limit);
+    let ret = f(nav);
+    let inner = nav.expose();
+    inner.limit = outer;
+
+    match inner.stopped.take() {
+        Some((interruption, partial)) => Err(interruption.error(partial)),
+        _ => Ok(ret),
+    }
+}
+
+/// Token to cancel running operations, possibly from another thread.
+#[derive(Debug, Clone, Default)]
+pub struct CancellationToken(Arc<AtomicBool>);
+impl CancellationToken {
+    #[allow(unused)]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Requests cancellation of all operations limited by this token.
+    #[allow(unused)]
+    pub fn cancel(&self) {
+        self.0.store(true, Ordering::SeqCst)
+    }
+
+    #[allow(unused)]
+    pub fn is_cancelled(&self) -> bool {
+        self.0.load(Ordering::SeqCst)
+    }
+}
+
+/// Deadline and cancellation token bounding an operation.
+#[derive(Debug, Clone, Default)]
+pub struct Limit {
+    deadline: Option<Instant>,
+    token: Option<CancellationToken>,
+}
+impl Limit {
+    /// Limit expiring after `timeout`.
+    #[allow(unused)]
+    pub fn timeout(timeout: Duration) -> Self {
+        Self::default().with_timeout(timeout)
+    }
+
+    /// Limit expiring at `deadline`.
+    #[allow(unused)]
+    pub fn deadline(deadline: Instant) -> Self {
+        Self::default().with_deadline(deadline)
+    }
+
+    /// Limit expiring once `token` is cancelled.
+    #[allow(unused)]
+    pub fn token(token: CancellationToken) -> Self {
+        Self::default().with_token(token)
+    }
+
+    #[allow(unused)]
+    pub fn with_timeout(self, timeout: Duration) -> Self {
+        self.with_deadline(Instant::now() + timeout)
+    }
+
+    #[allow(unused)]
+    pub fn with_deadline(mut self, deadline: Instant) -> Self {
+        self.deadline = Some(deadline);
+        self
+    }
+
+    #[allow(unused)]
+    pub fn with_token(mut self, token: CancellationToken) -> Self {
+        self.token = Some(token);
+        self
+    }
+
+    /// Returns time left until deadline.
+    #[allow(unused)]
+    pub fn remaining(&self) -> Option<Duration> {
+        self.deadline
+            .map(|d| d.saturating_duration_since(Instant::now()))
+    }
+
+    /// Returns limit expiring at `deadline` or earlier.
+    #[allow(unused)]
+    pub(crate) fn tighten(mut self, deadline: Instant) -> Self {
+        self.deadline = Some(self.deadline.map_or(deadline, |d| d.min(deadline)));
+        self
+    }
+
+    /// Returns whether limit expires at `deadline` or earlier.
+    #[allow(unused)]
+    pub(crate) fn expires_by(&self, deadline: Instant) -> bool {
+        self.deadline.is_some_and(|d| d <= deadline)
+    }
+
+    /// Returns why limit is exceeded, if it is.
+    pub(crate) fn exceeded(&self) -> Option<Interruption> {
+        if self.token.as_ref().is_some_and(|t| t.is_cancelled()) {
+            return Some(Interruption::Interrupted);
+        }
+        match self.deadline.is_some_and(|d| Instant::now() >= d) {
+            true => Some(Interruption::Timeout),
+            _ => None,
+        }
+    }
+
+    /// Waits via `ready` until the solver yields, checking limit in between.
+    pub(crate) fn poll(&self, mut ready: impl FnMut(Duration) -> bool) -> Option<Interruption> {
+        loop {
+            if let Some(interruption) = self.exceeded() {
+                return Some(interruption);
+            }
+            let slice = self.remaining().map_or(POLL, |r| r.min(POLL));
+            if ready(slice) {
+                return None;
+            }
+        }
+    }
+}
+
+/// Outcome of waiting for the next model of a solve call.
+pub(crate) enum Next<'a> {
+    Model(&'a Model),
+    Exhausted,
+    Stopped(Interruption),
+}
+
+/// Waits for the next model of `handle`, cancelling the solve call once `limit` is exceeded.
+pub(crate) fn next_model<'a>(
+    handle: &'a mut SolveHandle,
+    limit: &Option<Limit>,
+) -> Result<Next<'a>> {
+    if let Some(interruption) = limit.as_ref().and_then(|l| l.poll(|t| handle.wait(t))) {
+        handle.cancel()?;
+        return Ok(Next::Stopped(interruption));
+    }
+    match handle.model() {
+        Ok(Some(model)) => Ok(Next::Model(model)),
+        _ => Ok(Next::Exhausted),
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Interruption {
+    Timeout,
+    Interrupted,
+}
+impl Interruption {
+    pub(crate) fn error(self, partial: Partial) -> NavigatorError {
+        match self {
+            Self::Timeout => NavigatorError::Timeout(partial),
+            Self::Interrupted => NavigatorError::Interrupted(partial),
+        }
+    }
+}
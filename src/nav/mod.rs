@@ -1,12 +1,21 @@
+pub mod auto_navigation;
+pub mod enumeration;
 pub mod errors;
 pub mod faceted_navigation;
+pub mod formats;
+pub mod implications;
+pub mod limits;
 pub mod modes;
+pub mod ordering;
+pub mod probabilities;
+pub mod routes;
 mod utils;
 pub mod weighted_navigation;
 
 use crate::lex::*;
 
-use errors::Result;
+use errors::{Partial, Result};
+use limits::{next_model, Interruption, Limit, Next};
 
 use clingo::{Control, SolverLiteral, Symbol};
 use std::collections::{HashMap, HashSet};
@@ -60,6 +69,16 @@ pub fn enumerate_solutions_sharp<S: ToString>(
     nav.solutions_sharp(n, peek_on, f)
 }
 
+/// Returns `n` answer sets under current route conjunctively extended by `peek_on`.
+#[allow(unused)]
+pub fn answer_sets<S: ToString>(
+    nav: &mut impl Essential,
+    n: usize,
+    peek_on: impl Iterator<Item = S>,
+) -> Result<Vec<Vec<Symbol>>> {
+    nav.answer_sets(n, peek_on)
+}
+
 /// TODO
 #[allow(unused)]
 pub fn update(nav: &mut impl Essential) -> Result<()> {
@@ -69,6 +88,47 @@ pub fn update(nav: &mut impl Essential) -> Result<()> {
 #[derive(Debug, Clone)]
 struct FacetRepr(String);
 
+/// Returns body literal violated by facet `f`.
+fn negate(f: &FacetRepr) -> String {
+    let s = &f.0;
+    match s.starts_with("~") {
+        true => s[1..].to_owned(),
+        _ => format!("not {s}"),
+    }
+}
+
+/// Returns integrity constraints encoding route of `conjuncts` and `disjuncts`.
+fn constraints(conjuncts: &[FacetRepr], disjuncts: &[FacetRepr]) -> String {
+    let disjunction = disjunction(disjuncts);
+    conjuncts
+        .iter()
+        .map(|f| match disjunction.is_empty() {
+            true => format!(":- {}.", negate(f)),
+            _ => format!(":- {}, {}.", negate(f), disjunction),
+        })
+        .collect::<Vec<_>>() // TODO: mäh
+        .join("\n")
+}
+
+/// Returns body of integrity constraint violated if no disjunct holds.
+fn disjunction(disjuncts: &[FacetRepr]) -> String {
+    disjuncts
+        .iter()
+        .map(negate)
+        .collect::<Vec<_>>() // TODO: mäh
+        .join(",")
+}
+
+/// Returns `lp` extended by integrity constraints encoding route of `kind`, unless the route is
+/// conjunctive and therefore assumed.
+fn encode(lp: &str, kind: Kind, conjuncts: &[FacetRepr], disjuncts: &[FacetRepr]) -> String {
+    match (kind, disjuncts.is_empty()) {
+        (Kind::And, _) | (_, true) => lp.to_owned(),
+        (Kind::AndOr, _) => format!("{lp}\n{}", constraints(conjuncts, disjuncts)),
+        (Kind::Or, _) => format!("{lp}\n:- {}.", disjunction(disjuncts)),
+    }
+}
+
 pub struct Navigator {
     /// Clingo solver.
     ctl: Control,
@@ -77,7 +137,7 @@ pub struct Navigator {
     /// Disjunctively activated facets
     disjuncts: Vec<FacetRepr>,
     /// Active route.
-    route: String,
+    route: routes::Route,
     /// Current facets.
     #[allow(unused)]
     facets: HashSet<Symbol>,
@@ -85,6 +145,10 @@ pub struct Navigator {
     literals: HashMap<Symbol, SolverLiteral>,
     /// Input program and args.
     input: (String, Vec<String>),
+    /// Limit of running operation.
+    limit: Option<Limit>,
+    /// Reason and partial result of operation stopped by limit.
+    stopped: Option<(Interruption, Partial)>,
 }
 impl Navigator {
     #[allow(unused)]
@@ -110,60 +174,111 @@ impl Navigator {
             ctl,
             conjuncts: (vec![], vec![]),
             disjuncts: vec![],
-            route: "".to_owned(),
+            route: routes::Route::default(),
             facets: HashSet::default(),
             literals,
             input: (lp, args),
+            limit: None,
+            stopped: None,
         })
     }
 
-    fn assume(&mut self) -> Result<()> {
-        match !self.disjuncts.is_empty() {
-            true => {
-                let disjunction = self
-                    .disjuncts
-                    .iter()
-                    .map(|f| {
-                        let s = &f.0;
-                        match s.starts_with("~") {
-                            true => s[1..].to_owned(),
-                            _ => format!("not {s}"),
-                        }
-                    })
-                    .collect::<Vec<_>>() // TODO: mäh
-                    .join(",");
-                let lp = format!(
-                    "{}\n{}",
-                    self.input.0,
-                    self.conjuncts
-                        .1
-                        .iter()
-                        .map(|f| {
-                            let s = &f.0;
-                            let repr = match s.starts_with("~") {
-                                true => s[1..].to_owned(),
-                                _ => format!("not {s}"),
-                            };
-                            format!(":- {repr}, {}.", disjunction)
-                        })
-                        .collect::<Vec<_>>() // TODO: mäh
-                        .join("\n")
-                );
-                //println!("lp={:?}", &lp);
-                //println!("args={:?}", self.input.1.clone());
-
-                let mut ctl = clingo::control(self.input.1.clone())?;
-                ctl.add("base", &[], &lp)?;
-                ctl.ground(&[clingo::Part::new("base", vec![])?])?;
-
-                let mut literals = HashMap::new();
-                for atom in ctl.symbolic_atoms()?.iter()? {
-                    literals.insert(atom.symbol()?, atom.literal()?);
+    /// Returns solve mode, which is asynchronous if solve calls are limited.
+    fn solve_mode(&self) -> clingo::SolveMode {
+        match self.limit.is_some() {
+            true => clingo::SolveMode::ASYNC | clingo::SolveMode::YIELD,
+            _ => clingo::SolveMode::YIELD,
+        }
+    }
+
+    /// Returns error if running operation was already stopped by limit.
+    pub(crate) fn check_stopped(&self) -> Result<()> {
+        match &self.stopped {
+            Some((interruption, _)) => Err(interruption.error(Partial::None)),
+            _ => Ok(()),
+        }
+    }
+
+    /// Records that running operation was stopped with `partial` result.
+    fn stop(&mut self, interruption: Interruption, partial: Partial) -> errors::NavigatorError {
+        self.stopped = Some((interruption, partial.clone()));
+        interruption.error(partial)
+    }
+
+    /// Returns integrity constraints encoding route.
+    fn constraints(&self) -> String {
+        constraints(&self.conjuncts.1, &self.disjuncts)
+    }
+
+    /// Returns conjuncts and disjuncts of route of navigation of kind `from` in kind `to`, and
+    /// facets of route `to` cannot represent with same answer sets.
+    fn convert(&self, from: Kind, to: Kind) -> (Vec<FacetRepr>, Vec<FacetRepr>, Vec<FacetRepr>) {
+        let (mut conjuncts, mut disjuncts) = (self.conjuncts.1.clone(), self.disjuncts.clone());
+        let mut dropped = vec![];
+        if from == to {
+            return (conjuncts, disjuncts, dropped);
+        }
+        // route as conjunctive-disjunctive route
+        match from {
+            // without conjuncts, conjunctive-disjunctive routes hold regardless of their disjuncts
+            Kind::AndOr if conjuncts.is_empty() => disjuncts.clear(),
+            // a disjunction holds if its first disjunct or any other does
+            Kind::Or if !disjuncts.is_empty() => conjuncts.push(disjuncts.remove(0)),
+            _ => (),
+        }
+        match to {
+            Kind::And => dropped.append(&mut disjuncts),
+            Kind::AndOr => (),
+            Kind::Or => match conjuncts.len() {
+                // a single conjunct is a disjunct
+                0 | 1 => {
+                    conjuncts.append(&mut disjuncts);
+                    disjuncts = std::mem::take(&mut conjuncts);
                 }
+                _ => dropped.append(&mut conjuncts),
+            },
+        }
 
-                self.ctl = ctl;
+        (conjuncts, disjuncts, dropped)
+    }
 
-                Ok(())
+    /// Returns literal of facet `f`.
+    fn literal(&self, f: &FacetRepr) -> Option<SolverLiteral> {
+        match f.0.strip_prefix('~') {
+            Some(s) => parse(s).and_then(|sym| self.literals.get(&sym).map(|l| l.negate())),
+            _ => parse(&f.0).and_then(|sym| self.literals.get(&sym).copied()),
+        }
+    }
+
+    /// Encodes disjunctive route into program.
+    fn disjoin(&mut self) -> Result<()> {
+        let lp = encode(&self.input.0, Kind::Or, &[], &self.disjuncts);
+        self.reground(lp)
+    }
+
+    /// Replaces solver by one grounding `lp`.
+    fn reground(&mut self, lp: String) -> Result<()> {
+        let mut ctl = clingo::control(self.input.1.clone())?;
+        ctl.add("base", &[], &lp)?;
+        ctl.ground(&[clingo::Part::new("base", vec![])?])?;
+
+        let mut literals = HashMap::new();
+        for atom in ctl.symbolic_atoms()?.iter()? {
+            literals.insert(atom.symbol()?, atom.literal()?);
+        }
+
+        self.ctl = ctl;
+        self.literals = literals;
+
+        Ok(())
+    }
+
+    /// Encodes conjunctive-disjunctive route into program, or assumes it if it is conjunctive.
+    fn assume(&mut self) -> Result<()> {
+        match !self.disjuncts.is_empty() {
+            true => {
+                let lp = format!("{}\n{}", self.input.0, self.constraints());
+                self.reground(lp)
             }
             _ => self
                 .ctl
@@ -173,37 +288,40 @@ impl Navigator {
         }
     }
 
+    /// Activates facet of first pair of connective and facet in `delta`, ignoring it unless both
+    /// are valid.
     pub(crate) fn delta<S: ToString>(&mut self, mut delta: impl Iterator<Item = S>) {
         if let Some(token) = delta.next().map(|s| s.to_string()) {
             let f = delta.next().map(|s| s.to_string()).unwrap_or("".to_owned());
-            let (symbol, exc) = match f.starts_with('~') {
-                true => (f[1..].to_owned(), true),
-                _ => (f.clone(), false),
+            let connective = match token.as_str() {
+                "&" => routes::Connective::And,
+                "|" => routes::Connective::Or,
+                _ => {
+                    eprintln!("ignoring invalid input: {token}");
+                    return;
+                }
             };
-            match parse(&symbol).as_ref() {
-                Some(sym) => match self.literals.get(sym) {
-                    Some(lit) => {
-                        self.route = format!("{} {} {f}", self.route, token.clone());
-                        match token.as_str() {
-                            "&" => {
-                                match exc {
-                                    true => self.conjuncts.0.push(lit.negate()),
-                                    _ => self.conjuncts.0.push(*lit),
-                                }
-                                self.conjuncts.1.push(FacetRepr(f))
-                            }
-                            "|" => self.disjuncts.push(FacetRepr(f)),
-                            _ => {
-                                eprintln!("ignoring invalid input: {token}");
+            let Ok(facet) = f.parse::<routes::Facet>() else {
+                eprintln!("ignoring invalid input: {f}");
+                return;
+            };
+            match parse(&facet.atom).and_then(|sym| self.literals.get(&sym)) {
+                Some(lit) => {
+                    let repr = FacetRepr(facet.to_string());
+                    match connective {
+                        routes::Connective::And => {
+                            match facet.negated {
+                                true => self.conjuncts.0.push(lit.negate()),
+                                _ => self.conjuncts.0.push(*lit),
                             }
+                            self.conjuncts.1.push(repr)
                         }
+                        routes::Connective::Or => self.disjuncts.push(repr),
                     }
-                    _ => {
-                        eprintln!("ignoring unknown symbol: {symbol}");
-                    }
-                },
+                    self.route.push(connective, facet);
+                }
                 _ => {
-                    eprintln!("ignoring invalid input: {token}");
+                    eprintln!("ignoring unknown symbol: {}", facet.atom);
                 }
             }
         }
@@ -214,6 +332,62 @@ impl Navigator {
 pub enum Navigation {
     And(Navigator),
     AndOr(Navigator),
+    /// Navigation where answer sets satisfy some facet of the route.
+    Or(Navigator),
+}
+impl Navigation {
+    /// Converts navigation into `kind`, carrying over the route. Returns the facets dropped from
+    /// the route, since `kind` cannot represent them. If `strict`, no facet may be dropped. On
+    /// error, navigation is left unchanged.
+    #[allow(unused)]
+    pub fn switch(&mut self, kind: Kind, strict: bool) -> Result<Vec<String>> {
+        let from = self.kind();
+        let nav = self.expose();
+        let (conjuncts, disjuncts, dropped) = nav.convert(from, kind);
+        if strict && !dropped.is_empty() {
+            let dropped = dropped.iter().map(|f| f.0.as_str()).collect::<Vec<_>>();
+            return Err(errors::NavigatorError::InvalidInput(dropped.join(" ")));
+        }
+
+        // solver of previous kind may encode its route, so one is grounded for `kind`
+        let lp = encode(&nav.input.0, kind, &conjuncts, &disjuncts);
+        let mut next = Navigator::new(lp, nav.input.1.clone())?;
+        for (connective, fs) in [
+            (routes::Connective::And, &conjuncts),
+            (routes::Connective::Or, &disjuncts),
+        ] {
+            for f in fs.iter() {
+                next.route.push(connective, f.0.parse()?);
+            }
+        }
+        let literals = conjuncts.iter().filter_map(|f| next.literal(f)).collect();
+        next.conjuncts = (literals, conjuncts);
+        next.disjuncts = disjuncts;
+        next.facets = nav.facets.clone();
+        next.input.0 = nav.input.0.clone();
+        next.limit = nav.limit.clone();
+        next.stopped = nav.stopped.clone();
+        // conjunctive routes are assumed rather than encoded
+        if kind == Kind::AndOr && next.disjuncts.is_empty() {
+            next.assume()?;
+        }
+
+        *self = match kind {
+            Kind::And => Self::And(next),
+            Kind::AndOr => Self::AndOr(next),
+            Kind::Or => Self::Or(next),
+        };
+        Ok(dropped.into_iter().map(|f| f.0).collect())
+    }
+}
+
+/// Kind of navigation.
+#[allow(unused)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Kind {
+    And,
+    AndOr,
+    Or,
 }
 
 pub trait Essential {
@@ -233,20 +407,46 @@ pub trait Essential {
         peek_on: impl Iterator<Item = S>,
         f: impl FnMut(&String) -> bool,
     ) -> Result<()>;
+    /// Returns `n` answer sets under current route conjunctively extended by `peek_on`.
+    fn answer_sets<S: ToString>(
+        &mut self,
+        n: usize,
+        peek_on: impl Iterator<Item = S>,
+    ) -> Result<Vec<Vec<Symbol>>>;
     /// TODO
     fn read_route<S: ToString>(&self, peek_on: impl Iterator<Item = S>) -> Vec<SolverLiteral>;
+    /// Prepares solver for current route and returns literals to assume for current route
+    /// conjunctively extended by `peek_on`.
+    fn assumptions<S: ToString>(
+        &mut self,
+        peek_on: impl Iterator<Item = S>,
+    ) -> Result<Vec<SolverLiteral>>;
+    /// Returns integrity constraints encoding current route.
+    fn constraints(&self) -> String;
     /// TODO
     fn expose(&mut self) -> &mut Navigator;
     /// TODO
     fn update(&mut self) -> Result<()>;
-    /// TODO
+    /// Returns current route as fasb string, i.e., [`Essential::route`] with leading space.
     fn context(&self) -> String;
+    /// Returns current route.
+    ///
+    /// Facets of routes can be peeked on wherever facets are taken as strings, e.g.,
+    /// [`routes::Route::conjuncts`].
+    #[allow(unused)]
+    fn route(&self) -> routes::Route;
+    /// Returns kind of navigation.
+    #[allow(unused)]
+    fn kind(&self) -> Kind;
+    /// Activates all facets of `route` with their connectives, as typed counterpart of
+    /// [`Essential::delta`]. Rejects routes with unknown facets, or with facets whose connective
+    /// the kind of navigation ignores, without activating any facet.
+    #[allow(unused)]
+    fn activate(&mut self, route: &routes::Route) -> Result<()>;
 }
 impl Essential for Navigation {
     fn route_repr(&self) {
-        match &self {
-            Self::And(nav) | Self::AndOr(nav) => print!("{}", nav.route),
-        }
+        print!("{}", self.context())
     }
 
     fn clear(&mut self) -> Result<()> {
@@ -254,41 +454,40 @@ impl Essential for Navigation {
             Self::And(nav) => {
                 nav.conjuncts.0.clear();
                 nav.conjuncts.1.clear();
-                nav.route.clear();
+                nav.route = routes::Route::default();
                 Ok(())
             }
             Self::AndOr(nav) => {
                 nav.conjuncts.0.clear();
                 nav.conjuncts.1.clear();
                 nav.disjuncts.clear();
-                nav.route.clear();
+                nav.route = routes::Route::default();
                 nav.assume()
             }
+            Self::Or(nav) => {
+                nav.disjuncts.clear();
+                nav.route = routes::Route::default();
+                nav.disjoin()
+            }
         }
     }
 
     fn delta<S: ToString>(&mut self, delta: impl Iterator<Item = S>) {
-        match self {
-            Self::And(nav) | Self::AndOr(nav) => nav.delta(delta),
+        let mut delta = delta.map(|s| s.to_string()).peekable();
+        match (&mut *self, delta.peek().map(|t| t.as_str())) {
+            (Self::And(_), Some("|")) => {
+                eprintln!("ignoring disjunctive facet in conjunctive navigation")
+            }
+            (Self::Or(_), Some("&")) => {
+                eprintln!("ignoring conjunctive facet in disjunctive navigation")
+            }
+            (Self::And(nav) | Self::AndOr(nav) | Self::Or(nav), _) => nav.delta(delta),
         }
     }
 
     fn solutions<S: ToString>(&mut self, n: usize, peek_on: impl Iterator<Item = S>) -> Result<()> {
-        match self {
-            Self::And(nav) => {
-                let mut route = read_peek_on(peek_on, nav);
-                route.extend(nav.conjuncts.0.clone());
-
-                output_answer_sets(nav, &route, n)
-            }
-            Self::AndOr(nav) => {
-                let route = read_peek_on(peek_on, nav);
-
-                nav.assume()?;
-
-                output_answer_sets(nav, &route, n)
-            }
-        }
+        let route = self.assumptions(peek_on)?;
+        output_answer_sets(self.expose(), &route, n)
     }
 
     fn solutions_sharp<S: ToString>(
@@ -297,26 +496,22 @@ impl Essential for Navigation {
         peek_on: impl Iterator<Item = S>,
         f: impl FnMut(&String) -> bool,
     ) -> Result<()> {
-        match self {
-            Self::And(nav) => {
-                let mut route = read_peek_on(peek_on, nav);
-                route.extend(nav.conjuncts.0.clone());
-
-                output_answer_sets_sharp(nav, &route, n, f)
-            }
-            Self::AndOr(nav) => {
-                let route = read_peek_on(peek_on, nav);
-
-                nav.assume()?;
+        let route = self.assumptions(peek_on)?;
+        output_answer_sets_sharp(self.expose(), &route, n, f)
+    }
 
-                output_answer_sets_sharp(nav, &route, n, f)
-            }
-        }
+    fn answer_sets<S: ToString>(
+        &mut self,
+        n: usize,
+        peek_on: impl Iterator<Item = S>,
+    ) -> Result<Vec<Vec<Symbol>>> {
+        let route = self.assumptions(peek_on)?;
+        collect_answer_sets(self.expose(), &route, n)
     }
 
     fn read_route<S: ToString>(&self, peek_on: impl Iterator<Item = S>) -> Vec<SolverLiteral> {
         match self {
-            Self::And(nav) | Self::AndOr(nav) => peek_on
+            Self::And(nav) | Self::AndOr(nav) | Self::Or(nav) => peek_on
                 .map(|f| {
                     let s = f.to_string();
                     match s.starts_with("~") {
@@ -334,9 +529,44 @@ impl Essential for Navigation {
         }
     }
 
+    fn assumptions<S: ToString>(
+        &mut self,
+        peek_on: impl Iterator<Item = S>,
+    ) -> Result<Vec<SolverLiteral>> {
+        match self {
+            Self::And(nav) => {
+                let mut route = read_peek_on(peek_on, nav);
+                route.extend(nav.conjuncts.0.clone());
+
+                Ok(route)
+            }
+            Self::AndOr(nav) => {
+                nav.assume()?;
+
+                Ok(read_peek_on(peek_on, nav))
+            }
+            Self::Or(nav) => {
+                nav.disjoin()?;
+
+                Ok(read_peek_on(peek_on, nav))
+            }
+        }
+    }
+
+    fn constraints(&self) -> String {
+        match self {
+            Self::And(nav) => constraints(&nav.conjuncts.1, &[]),
+            Self::AndOr(nav) => constraints(&nav.conjuncts.1, &nav.disjuncts),
+            Self::Or(nav) => match nav.disjuncts.is_empty() {
+                true => String::new(),
+                _ => format!(":- {}.", disjunction(&nav.disjuncts)),
+            },
+        }
+    }
+
     fn expose(&mut self) -> &mut Navigator {
         match self {
-            Self::And(nav) | Self::AndOr(nav) => nav,
+            Self::And(nav) | Self::AndOr(nav) | Self::Or(nav) => nav,
         }
     }
 
@@ -344,57 +574,94 @@ impl Essential for Navigation {
         match self {
             Self::And(_) => Ok(()),
             Self::AndOr(nav) => nav.assume(),
+            Self::Or(nav) => nav.disjoin(),
         }
     }
 
     fn context(&self) -> String {
         match self {
-            Self::And(nav) | Self::AndOr(nav) => nav.route.clone(),
+            Self::And(nav) | Self::AndOr(nav) | Self::Or(nav) => match nav.route.is_empty() {
+                true => String::new(),
+                _ => format!(" {}", nav.route),
+            },
         }
     }
+
+    fn route(&self) -> routes::Route {
+        match self {
+            Self::And(nav) | Self::AndOr(nav) | Self::Or(nav) => nav.route.clone(),
+        }
+    }
+
+    fn kind(&self) -> Kind {
+        match self {
+            Self::And(_) => Kind::And,
+            Self::AndOr(_) => Kind::AndOr,
+            Self::Or(_) => Kind::Or,
+        }
+    }
+
+    fn activate(&mut self, route: &routes::Route) -> Result<()> {
+        let ignored = match self.kind() {
+            Kind::And => route.disjuncts().next(),
+            Kind::AndOr => None,
+            Kind::Or => route.conjuncts().next(),
+        };
+        if ignored.is_some() {
+            return Err(errors::NavigatorError::InvalidInput(route.to_string()));
+        }
+        probabilities::literals(self.expose(), route.facets())?;
+        for (connective, facet) in route.iter() {
+            let token = match connective {
+                routes::Connective::And => "&",
+                routes::Connective::Or => "|",
+            };
+            self.delta([token.to_owned(), facet.to_string()].iter());
+        }
+        self.update()
+    }
 }
 
 fn output_answer_sets(nav: &mut Navigator, route: &[SolverLiteral], n: usize) -> Result<()> {
-    let mut handle = nav.ctl.fasb_solve(clingo::SolveMode::YIELD, &route)?;
+    nav.check_stopped()?;
+    let (mode, limit) = (nav.solve_mode(), nav.limit.clone());
+    let mut handle = nav.ctl.fasb_solve(mode, &route)?;
     let mut i = 1;
-
-    match n == 0 {
-        true => {
-            while let Ok(Some(answer_set)) = handle.model() {
-                println!("Solution {:?}: ", i);
-                let atoms = answer_set.symbols(clingo::ShowType::SHOWN)?;
-                atoms.iter().for_each(|atom| {
-                    print!("{} ", atom.to_string());
-                });
-                println!();
-
-                i += 1;
-                handle.resume()?;
-            }
-        }
-        _ => {
-            while let Ok(Some(answer_set)) = handle.model() {
-                println!("Solution {:?}: ", i);
-                let atoms = answer_set.symbols(clingo::ShowType::SHOWN)?;
-                atoms.iter().for_each(|atom| {
-                    print!("{} ", atom.to_string());
-                });
-                println!();
-
-                i += 1;
-                if i > n {
-                    break;
-                }
-                handle.resume()?;
+    let mut stopped = None;
+
+    loop {
+        let answer_set = match next_model(&mut handle, &limit)? {
+            Next::Model(model) => model,
+            Next::Exhausted => break,
+            Next::Stopped(interruption) => {
+                stopped = Some(interruption);
+                break;
             }
+        };
+        println!("Solution {:?}: ", i);
+        let atoms = answer_set.symbols(clingo::ShowType::SHOWN)?;
+        atoms.iter().for_each(|atom| {
+            print!("{} ", atom.to_string());
+        });
+        println!();
+
+        i += 1;
+        if n > 0 && i > n {
+            break;
         }
+        handle.resume()?;
     }
 
     println!("found {:?}", i - 1);
 
-    return handle
+    handle
         .close()
-        .map_err(|e| errors::NavigatorError::Clingo(e));
+        .map_err(|e| errors::NavigatorError::Clingo(e))?;
+
+    match stopped {
+        Some(interruption) => Err(nav.stop(interruption, Partial::Count(i - 1))),
+        _ => Ok(()),
+    }
 }
 
 fn output_answer_sets_sharp(
@@ -403,46 +670,81 @@ fn output_answer_sets_sharp(
     n: usize,
     mut f: impl FnMut(&String) -> bool,
 ) -> Result<()> {
-    let mut handle = nav.ctl.fasb_solve(clingo::SolveMode::YIELD, &route)?;
+    nav.check_stopped()?;
+    let (mode, limit) = (nav.solve_mode(), nav.limit.clone());
+    let mut handle = nav.ctl.fasb_solve(mode, &route)?;
     let mut i = 1;
-
-    match n == 0 {
-        true => {
-            while let Ok(Some(answer_set)) = handle.model() {
-                println!("Solution {:?}: ", i);
-                let atoms = answer_set.symbols(clingo::ShowType::SHOWN)?;
-                for atom in atoms.iter().map(|atom| atom.to_string()).filter(&mut f) {
-                    print!("{} ", atom);
-                }
-                println!();
-
-                i += 1;
-                handle.resume()?;
+    let mut stopped = None;
+
+    loop {
+        let answer_set = match next_model(&mut handle, &limit)? {
+            Next::Model(model) => model,
+            Next::Exhausted => break,
+            Next::Stopped(interruption) => {
+                stopped = Some(interruption);
+                break;
             }
+        };
+        println!("Solution {:?}: ", i);
+        let atoms = answer_set.symbols(clingo::ShowType::SHOWN)?;
+        for atom in atoms.iter().map(|atom| atom.to_string()).filter(&mut f) {
+            print!("{} ", atom);
         }
-        _ => {
-            while let Ok(Some(answer_set)) = handle.model() {
-                println!("Solution {:?}: ", i);
-                let atoms = answer_set.symbols(clingo::ShowType::SHOWN)?;
-                for atom in atoms.iter().map(|atom| atom.to_string()).filter(&mut f) {
-                    print!("{} ", atom);
-                }
-                println!();
+        println!();
 
-                i += 1;
-                if i > n {
-                    break;
-                }
-                handle.resume()?;
-            }
+        i += 1;
+        if n > 0 && i > n {
+            break;
         }
+        handle.resume()?;
     }
 
     println!("found {:?}", i - 1);
 
-    return handle
+    handle
         .close()
-        .map_err(|e| errors::NavigatorError::Clingo(e));
+        .map_err(|e| errors::NavigatorError::Clingo(e))?;
+
+    match stopped {
+        Some(interruption) => Err(nav.stop(interruption, Partial::Count(i - 1))),
+        _ => Ok(()),
+    }
+}
+
+/// Returns `n` answer sets, or all answer sets if `n` is 0.
+pub(crate) fn collect_answer_sets(
+    nav: &mut Navigator,
+    route: &[SolverLiteral],
+    n: usize,
+) -> Result<Vec<Vec<Symbol>>> {
+    nav.check_stopped()?;
+    let (mode, limit) = (nav.solve_mode(), nav.limit.clone());
+    let mut handle = nav.ctl.fasb_solve(mode, route)?;
+    let mut answer_sets = vec![];
+    let mut stopped = None;
+
+    loop {
+        let answer_set = match next_model(&mut handle, &limit)? {
+            Next::Model(model) => model,
+            Next::Exhausted => break,
+            Next::Stopped(interruption) => {
+                stopped = Some(interruption);
+                break;
+            }
+        };
+        answer_sets.push(answer_set.symbols(clingo::ShowType::SHOWN)?);
+        if n > 0 && answer_sets.len() >= n {
+            break;
+        }
+        handle.resume()?;
+    }
+
+    handle.close().map_err(errors::NavigatorError::Clingo)?;
+
+    match stopped {
+        Some(interruption) => Err(nav.stop(interruption, Partial::AnswerSets(answer_sets))),
+        _ => Ok(answer_sets),
+    }
 }
 
 /// Returns answer set count.
@@ -452,32 +754,36 @@ pub(crate) fn answer_set_count(
     route: &[SolverLiteral],
     upper_bound: usize,
 ) -> Result<usize> {
-    let mut handle = nav.ctl.fasb_solve(clingo::SolveMode::YIELD, &route)?;
+    nav.check_stopped()?;
+    let (mode, limit) = (nav.solve_mode(), nav.limit.clone());
+    let mut handle = nav.ctl.fasb_solve(mode, &route)?;
     let mut i = 0;
-
-    match upper_bound == 0 {
-        true => {
-            while let Ok(Some(_)) = handle.model() {
-                i += 1;
-                handle.resume()?;
-            }
-        }
-        _ => {
-            while let Ok(Some(_)) = handle.model() {
-                i += 1;
-                if i > upper_bound {
-                    break;
-                }
-                handle.resume()?;
+    let mut stopped = None;
+
+    loop {
+        let _ = match next_model(&mut handle, &limit)? {
+            Next::Model(model) => model,
+            Next::Exhausted => break,
+            Next::Stopped(interruption) => {
+                stopped = Some(interruption);
+                break;
             }
+        };
+        i += 1;
+        if upper_bound > 0 && i > upper_bound {
+            break;
         }
+        handle.resume()?;
     }
 
     handle
         .close()
         .map_err(|e| errors::NavigatorError::Clingo(e))?;
 
-    Ok(i)
+    match stopped {
+        Some(interruption) => Err(nav.stop(interruption, Partial::Count(i))),
+        _ => Ok(i),
+    }
 }
 
 fn read_peek_on<S: ToString>(
@@ -627,6 +933,745 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn formats_health() -> Result<()> {
+        use formats::Format;
+        use serde_json::Value;
+
+        let nav = Navigator::new("a;b. c;d :- b. e.", vec!["0".to_string()])?;
+        let mut anav = Navigation::And(nav);
+
+        let json: Value = serde_json::from_str(&formats::answer_sets(
+            &mut anav,
+            2,
+            std::iter::empty::<String>(),
+            &Format::Clingo,
+        )?)
+        .expect("invalid JSON");
+        assert_eq!(json["Result"], "SATISFIABLE");
+        assert_eq!(
+            json["Call"][0]["Witnesses"].as_array().map(|w| w.len()),
+            Some(2)
+        );
+        assert_eq!(json["Models"]["Number"], 2);
+        assert_eq!(json["Models"]["More"], "yes");
+
+        let json: Value = serde_json::from_str(&formats::answer_sets(
+            &mut anav,
+            0,
+            ["a", "b"].iter(),
+            &Format::Clingo,
+        )?)
+        .expect("invalid JSON");
+        assert_eq!(json["Result"], "UNSATISFIABLE");
+        assert_eq!(json["Models"]["Number"], 0);
+        assert_eq!(json["Models"]["More"], "no");
+
+        let lines = formats::answer_sets(
+            &mut anav,
+            0,
+            std::iter::empty::<String>(),
+            &Format::JsonLines,
+        )?;
+        let lines = lines
+            .lines()
+            .map(|l| serde_json::from_str::<Value>(l).expect("invalid JSON line"))
+            .collect::<Vec<_>>();
+        assert_eq!(lines.len(), 3);
+        assert!(lines.iter().all(|l| l["type"] == "answer_set"));
+
+        let csv = formats::answer_sets(&mut anav, 0, ["b"].iter(), &Format::Csv)?;
+        let mut rows = csv.lines();
+        assert_eq!(rows.next(), Some("answer_set,atom"));
+        assert_eq!(rows.count(), 6);
+
+        let csv = formats::facets(&mut anav, std::iter::empty::<String>(), &Format::Csv)
+            .expect("no facets");
+        assert_eq!(csv, "facet\na\nb\nc\nd");
+
+        let csv = formats::stats(&mut anav, ["b"].iter(), &Format::Csv).expect("no stats");
+        assert_eq!(csv, "brave,cautious,facets\n4,2,2");
+
+        let json: Value =
+            serde_json::from_str(&formats::context(&anav, &Format::Clingo)).expect("invalid JSON");
+        assert_eq!(json["Route"], "");
+
+        Ok(())
+    }
+
+    #[test]
+    fn limits_health() -> Result<()> {
+        use limits::{within, CancellationToken, Limit};
+        use std::time::Duration;
+
+        let nav = Navigator::new("a;b. c;d :- b. e.", vec!["0".to_string()])?;
+        let mut anav = Navigation::And(nav);
+
+        let count = within(&mut anav, Limit::timeout(Duration::ZERO), |nav| {
+            answer_set_count(nav.expose(), &[], 0)
+        });
+        assert!(matches!(
+            count,
+            Err(errors::NavigatorError::Timeout(Partial::Count(0)))
+        ));
+
+        let token = CancellationToken::new();
+        token.cancel();
+        let solutions = within(&mut anav, Limit::token(token), |nav| {
+            nav.answer_sets(0, std::iter::empty::<String>())
+        });
+        assert!(matches!(
+            solutions,
+            Err(errors::NavigatorError::Interrupted(Partial::AnswerSets(_)))
+        ));
+
+        let count = within(&mut anav, Limit::timeout(Duration::from_secs(60)), |nav| {
+            answer_set_count(nav.expose(), &[], 0)
+        })??;
+        assert_eq!(count, 3);
+
+        Ok(())
+    }
+
+    #[test]
+    fn scoring_health() -> Result<()> {
+        use modes::{Guide, Mode, Scoring};
+
+        struct Negations;
+        impl Scoring for Negations {
+            fn score(
+                &mut self,
+                _nav: &mut Navigator,
+                facet: &str,
+                _route: &[SolverLiteral],
+                _bound: Option<f64>,
+            ) -> Option<f64> {
+                Some(match facet.starts_with('~') {
+                    true => 0.0,
+                    _ => 1.0,
+                })
+            }
+        }
+
+        let mut nav = Navigator::new("a;b. c;d :- b. e.", vec!["0".to_string()])?;
+        let (f, _) = Mode::MaxScored(Box::new(Negations))
+            .step(&mut nav, &mut None)
+            .ok_or(errors::NavigatorError::None)?;
+        assert!(f.starts_with('~'));
+
+        let (f, _) = Mode::MinScored(Box::new(Negations))
+            .step(&mut nav, &mut None)
+            .ok_or(errors::NavigatorError::None)?;
+        assert!(!f.starts_with('~'));
+
+        Ok(())
+    }
+
+    #[test]
+    fn balanced_health() -> Result<()> {
+        use modes::{splits, Guide, InformationGain, Mode};
+        use weighted_navigation::Weight;
+
+        let mut nav = Navigator::new("a;b. c;d :- b. e.", vec!["0".to_string()])?;
+        let xs = splits(&mut nav, Weight::AnswerSetCounting).ok_or(errors::NavigatorError::None)?;
+        xs.iter()
+            .for_each(|x| println!("{} {:.2} {:.2}", x.facet, x.ratio(), x.entropy()));
+        assert_eq!(xs.len(), 4);
+        assert!(xs[0].entropy() >= xs[3].entropy());
+
+        let mut mode = Mode::Balanced(InformationGain::new(Weight::AnswerSetCounting));
+        let (f, _) = mode
+            .step(&mut nav, &mut None)
+            .ok_or(errors::NavigatorError::None)?;
+        assert!(["a", "b", "c", "d"].contains(&f.trim_start_matches('~')));
+
+        Ok(())
+    }
+
+    #[test]
+    fn lookahead_health() -> Result<()> {
+        use modes::{Guide, Lookahead, Mode};
+        use weighted_navigation::Weight;
+
+        // answer sets {a,b,c,d}, {a,b,d}, {a,c}, {b,e}
+        let lp = "{a;b;c}. d :- a, b. e :- not a. :- not a, c. :- not b, not c.";
+        let mut nav = Navigator::new(lp, vec!["0".to_string()])?;
+        let (f, _) = Mode::MaxWeighted(Weight::CcCounting)
+            .step(&mut nav, &mut None)
+            .ok_or(errors::NavigatorError::None)?;
+        assert_eq!(f, "~d");
+        let (f, _) = Mode::Lookahead(Lookahead::new(Weight::CcCounting, 2, 8))
+            .step(&mut nav, &mut None)
+            .ok_or(errors::NavigatorError::None)?;
+        assert_eq!(f, "b");
+
+        let sequence = |depth, beam| Lookahead::new(Weight::CcCounting, depth, beam);
+        assert_eq!(
+            sequence(1, 8).sequence(&mut nav),
+            Some((vec!["~d".to_owned()], 0.0))
+        );
+        assert_eq!(
+            sequence(2, 8).sequence(&mut nav),
+            Some((vec!["b".to_owned(), "~c".to_owned()], 1.0))
+        );
+        // pruning to the best route per step follows greedy choice
+        assert_eq!(
+            sequence(2, 1).sequence(&mut nav),
+            Some((vec!["~d".to_owned(), "a".to_owned()], 2.0))
+        );
+        assert_eq!(
+            sequence(2, 8).maximize().sequence(&mut nav),
+            Some((vec!["d".to_owned(), "c".to_owned()], 4.0))
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn goal_health() -> Result<()> {
+        use modes::{Goal, Guide, Mode};
+
+        let mut nav = Navigator::new("a;b. c;d :- b. e.", vec!["0".to_string()])?;
+        let mut mode = Mode::GoalOriented(Goal::new(["d"].iter()));
+        let (f, _) = mode
+            .step(&mut nav, &mut None)
+            .ok_or(errors::NavigatorError::None)?;
+        assert_eq!(f, "d");
+
+        let mut mode =
+            Mode::GoalOriented(Goal::assignment([("a", false), ("c", false)].into_iter()));
+        let (f, _) = mode
+            .step(&mut nav, &mut None)
+            .ok_or(errors::NavigatorError::None)?;
+        assert_eq!(f, "d");
+
+        Ok(())
+    }
+
+    #[test]
+    fn anytime_health() -> Result<()> {
+        use modes::{Goal, Guide, Mode};
+        use std::time::Duration;
+
+        let mut nav = Navigator::new("a;b. c;d :- b. e.", vec!["0".to_string()])?;
+        let mut mode = Mode::GoalOriented(Goal::new(["d"].iter()));
+
+        let anytime = mode
+            .step_within(&mut nav, Duration::ZERO)
+            .ok_or(errors::NavigatorError::None)?;
+        assert_eq!((anytime.scored, anytime.candidates), (0, 8));
+        assert!(anytime.facet.is_some() && anytime.score.is_none());
+        assert!(nav.check_stopped().is_ok());
+
+        let (f, _) = mode
+            .step(&mut nav, &mut None)
+            .ok_or(errors::NavigatorError::None)?;
+        let anytime = mode
+            .step_within(&mut nav, Duration::from_secs(60))
+            .ok_or(errors::NavigatorError::None)?;
+        assert_eq!(anytime.facet.map(|(g, _)| g), Some(f));
+        assert_eq!(anytime.score, Some(0.0));
+        assert!(anytime.scored > 0);
+
+        Ok(())
+    }
+
+    #[test]
+    fn auto_navigation_health() -> Result<()> {
+        use modes::Mode;
+        use weighted_navigation::Weight;
+
+        let nav = Navigator::new("a;b. c;d :- b. e.", vec!["0".to_string()])?;
+        let mut anav = Navigation::And(nav);
+        let mut mode = Mode::MaxWeighted(Weight::FacetCounting);
+        let trace = auto_navigation::navigate(&mut anav, &mut mode)?;
+        assert_eq!((trace.facets, trace.answer_sets), (4, 3));
+        assert!(trace.is_complete());
+        assert_eq!(trace.remaining(), 0);
+        assert_eq!(trace.steps.len(), anav.expose().conjuncts.0.len());
+
+        // activated facets narrow conjunctive routes only
+        let nav = Navigator::new("a;b. c;d :- b. e.", vec!["0".to_string()])?;
+        let mut onav = Navigation::Or(nav);
+        assert!(auto_navigation::navigate(&mut onav, &mut mode).is_err());
+        assert!(onav.context().is_empty());
+        let nav = Navigator::new("a;b. c;d :- b. e.", vec!["0".to_string()])?;
+        let mut aonav = Navigation::AndOr(nav);
+        aonav.delta(["&", "b"].iter());
+        aonav.delta(["|", "a"].iter());
+        assert!(auto_navigation::navigate(&mut aonav, &mut mode).is_err());
+        assert_eq!(aonav.context(), " & b | a");
+
+        Ok(())
+    }
+
+    #[test]
+    fn random_health() -> Result<()> {
+        let nav = Navigator::new("a;b. c;d :- b. e.", vec!["0".to_string()])?;
+        let mut anav = Navigation::And(nav);
+        let walks = auto_navigation::simulate(&mut anav, 8, 42)?;
+        assert_eq!((walks.lengths.len(), walks.incomplete), (8, 0));
+        assert!(walks.min().is_some_and(|l| l >= 1) && walks.max().is_some_and(|l| l <= 2));
+        assert_eq!(walks.distribution().values().sum::<usize>(), 8);
+        assert!(anav.context().is_empty());
+
+        let again = auto_navigation::simulate(&mut anav, 8, 42)?;
+        assert_eq!(walks.lengths, again.lengths);
+
+        let nav = Navigator::new("a;b. c;d :- b. e.", vec!["0".to_string()])?;
+        let mut onav = Navigation::Or(nav);
+        onav.delta(["|", "a"].iter());
+        assert!(auto_navigation::simulate(&mut onav, 8, 42).is_err());
+        assert_eq!(onav.context(), " | a");
+
+        Ok(())
+    }
+
+    #[test]
+    fn rank_health() -> Result<()> {
+        use modes::{Guide, Mode};
+        use weighted_navigation::Weight;
+
+        let mut nav = Navigator::new("a;b. c;d :- b. e.", vec!["0".to_string()])?;
+        let mut mode = Mode::MaxWeighted(Weight::AnswerSetCounting);
+        let ranking = mode.rank(&mut nav).ok_or(errors::NavigatorError::None)?;
+        let expected = ["a", "c", "d", "~b", "b", "~a", "~c", "~d"]
+            .into_iter()
+            .zip([1.0, 1.0, 1.0, 1.0, 2.0, 2.0, 2.0, 2.0])
+            .map(|(f, s)| (f.to_owned(), s))
+            .collect::<Vec<_>>();
+        assert_eq!(ranking, expected);
+
+        Ok(())
+    }
+
+    #[test]
+    fn ordering_health() -> Result<()> {
+        use faceted_navigation::FacetedNavigation;
+        use ordering::Order;
+
+        let nav = Navigator::new(
+            "p(b;a(10);a(2)). q;r. { a(1,2); a(3) }.",
+            vec!["0".to_string()],
+        )?;
+        let mut anav = Navigation::And(nav);
+        let mut fs = |order| {
+            anav.sorted_facets(std::iter::empty::<String>(), order)
+                .map(|fs| fs.iter().map(|f| f.to_string()).collect::<Vec<_>>())
+        };
+        assert_eq!(
+            fs(Order::Symbol),
+            Some(["a(1,2)", "a(3)", "q", "r"].map(|x| x.to_owned()).to_vec())
+        );
+        assert_eq!(
+            fs(Order::Signature),
+            Some(["a(3)", "a(1,2)", "q", "r"].map(|x| x.to_owned()).to_vec())
+        );
+        let bc = anav
+            .brave_consequences(std::iter::empty::<String>())
+            .map(|xs| xs.iter().map(|x| x.to_string()).collect::<Vec<_>>());
+        assert_eq!(
+            bc,
+            Some(
+                ["a(1,2)", "a(3)", "p(a(10))", "p(a(2))", "p(b)", "q", "r"]
+                    .map(|x| x.to_owned())
+                    .to_vec()
+            )
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn marginals_health() -> Result<()> {
+        let nav = Navigator::new("a;b. c;d :- b. e.", vec!["0".to_string()])?;
+        let mut anav = Navigation::And(nav);
+        let enumerated = probabilities::marginals(&mut anav, 8)?;
+        let counted = probabilities::marginals(&mut anav, 0)?;
+        assert_eq!(enumerated, counted);
+        let counts = enumerated
+            .iter()
+            .map(|m| (m.facet.as_str(), m.count, m.total))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            counts,
+            vec![("a", 1, 3), ("b", 2, 3), ("c", 1, 3), ("d", 1, 3)]
+        );
+
+        let nav = Navigator::new("a;b. c;d :- b. e.", vec!["0".to_string()])?;
+        let mut aonav = Navigation::AndOr(nav);
+        aonav.delta(["&", "a"].iter());
+        aonav.delta(["|", "d"].iter());
+        aonav.update()?;
+        let counts = probabilities::marginals(&mut aonav, 0)?
+            .iter()
+            .map(|m| (m.facet.clone(), m.count, m.total))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            counts,
+            vec![
+                ("a".to_owned(), 1, 2),
+                ("b".to_owned(), 1, 2),
+                ("d".to_owned(), 1, 2)
+            ]
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn conditional_health() -> Result<()> {
+        let nav = Navigator::new("a;b. c;d :- b. e.", vec!["0".to_string()])?;
+        let mut anav = Navigation::And(nav);
+        let p = probabilities::conditional(&mut anav, ["c"].iter(), ["~a"].iter())?;
+        assert_eq!((p.numerator, p.denominator), (1, 2));
+        assert_eq!(p.probability(), Some(0.5));
+
+        anav.delta(["&", "b"].iter());
+        let p = probabilities::conditional(&mut anav, ["c", "~d"].iter(), [].iter())?;
+        assert_eq!((p.numerator, p.denominator), (1, 2));
+        let p = probabilities::conditional(&mut anav, ["e"].iter(), ["a"].iter())?;
+        assert_eq!(p.probability(), None);
+        assert!(probabilities::conditional(&mut anav, ["x"].iter(), [].iter()).is_err());
+
+        let nav = Navigator::new("a;b. c;d :- b. e.", vec!["0".to_string()])?;
+        let mut aonav = Navigation::AndOr(nav);
+        aonav.delta(["&", "a"].iter());
+        aonav.delta(["|", "d"].iter());
+        aonav.update()?;
+        let p = probabilities::conditional(&mut aonav, ["a"].iter(), [].iter())?;
+        assert_eq!((p.numerator, p.denominator), (1, 2));
+
+        Ok(())
+    }
+
+    #[test]
+    fn weighted_health() -> Result<()> {
+        use modes::Scoring;
+        use weighted_navigation::{Weight, Weights};
+
+        let nav = Navigator::new("a;b. c;d :- b. e.", vec!["0".to_string()])?;
+        let mut anav = Navigation::And(nav);
+        let weights = Weights::probabilities([("a", 0.5), ("c", 0.5)].into_iter())?;
+        let wmc = probabilities::weighted_marginals(&mut anav, &weights)?;
+        // {a,e}: .5 * .5, {b,c,e}: .5 * .5, {b,d,e}: .5 * .5
+        assert_eq!(wmc.count, 0.75);
+        assert_eq!(wmc.marginals[0], ("a".to_owned(), 1.0 / 3.0));
+
+        let weights = Weights::from_fn(["d"].iter(), |_, contained| match contained {
+            true => 2.0,
+            _ => 1.0,
+        })?;
+        let mut weight = Weight::WeightedModelCounting(weights);
+        let nav = anav.expose();
+        let route = nav.conjuncts.0.clone();
+        assert_eq!(weight.score(nav, "", &route, None), Some(4.0));
+        assert_eq!(
+            weighted_navigation::count(&mut weight, &mut anav, ["d"].iter()),
+            None
+        );
+        assert_eq!(
+            weighted_navigation::weigh(&mut weight, &mut anav, ["d"].iter()),
+            Some(2.0)
+        );
+
+        let nav = Navigator::new("a;b. c;d :- b. e.", vec!["0".to_string()])?;
+        let mut aonav = Navigation::AndOr(nav);
+        aonav.delta(["&", "a"].iter());
+        aonav.delta(["|", "d"].iter());
+        aonav.update()?;
+        assert_eq!(
+            weighted_navigation::weigh(&mut weight, &mut aonav, std::iter::empty::<String>()),
+            Some(3.0)
+        );
+
+        // probabilities below 1 still yield informative splits
+        let weights = Weights::probabilities([("a", 0.5), ("c", 0.5)].into_iter())?;
+        let xs = modes::splits(anav.expose(), Weight::WeightedModelCounting(weights))
+            .ok_or(errors::NavigatorError::None)?;
+        assert_eq!(xs.len(), 4);
+        assert!(xs.iter().all(|x| x.entropy() > 0.0));
+
+        Ok(())
+    }
+
+    #[test]
+    fn sample_health() -> Result<()> {
+        let nav = Navigator::new("a;b. c;d :- b. e.", vec!["0".to_string()])?;
+        let mut anav = Navigation::And(nav);
+        let xs = enumeration::sample(&mut anav, 5, 7, ["b"].iter())?;
+        assert_eq!(xs.len(), 5);
+        assert!(xs.iter().all(|x| x.len() == 3));
+        assert_eq!(xs, enumeration::sample(&mut anav, 5, 7, ["b"].iter())?);
+        assert!(enumeration::sample(&mut anav, 5, 7, ["a", "b"].iter())?.is_empty());
+
+        let nav = Navigator::new("a;b. c;d :- b. e.", vec!["0".to_string()])?;
+        let mut aonav = Navigation::AndOr(nav);
+        aonav.delta(["&", "a"].iter());
+        aonav.delta(["|", "d"].iter());
+        let xs = enumeration::sample(&mut aonav, 2, 7, ["b"].iter())?;
+        assert!(xs.iter().all(|x| x.iter().any(|a| a.to_string() == "d")));
+        assert_eq!(xs.len(), 2);
+
+        Ok(())
+    }
+
+    #[test]
+    fn diverse_health() -> Result<()> {
+        let nav = Navigator::new("a;b. c;d :- b. e.", vec!["0".to_string()])?;
+        let mut anav = Navigation::And(nav);
+        let xs = enumeration::diverse(&mut anav, 2, 8, 0, std::iter::empty::<String>())?;
+        assert_eq!(xs.len(), 2);
+        // {a,e} differs from both other answer sets in three facets
+        assert!(xs.iter().any(|x| x.iter().any(|a| a.to_string() == "a")));
+        let xs = enumeration::diverse(&mut anav, 5, 8, 0, std::iter::empty::<String>())?;
+        assert_eq!(xs.len(), 3);
+        assert!(enumeration::diverse(&mut anav, 2, 0, 0, std::iter::empty::<String>()).is_err());
+
+        let nav = Navigator::new("a;b. c;d :- b. e.", vec!["0".to_string()])?;
+        let mut aonav = Navigation::AndOr(nav);
+        aonav.delta(["&", "a"].iter());
+        aonav.delta(["|", "d"].iter());
+        let xs = enumeration::diverse(&mut aonav, 5, 8, 0, std::iter::empty::<String>())?;
+        assert_eq!(xs.len(), 2);
+
+        Ok(())
+    }
+
+    #[test]
+    fn closest_health() -> Result<()> {
+        let nav = Navigator::new("a;b. c;d :- b. e.", vec!["0".to_string()])?;
+        let mut anav = Navigation::And(nav);
+        anav.delta(["&", "~d"].iter());
+        let xs = enumeration::closest(&mut anav, ["a", "c", "~e"].iter(), 0)?;
+        assert_eq!(xs.len(), 2);
+        assert!(xs.iter().all(|x| x.dropped.len() == 2));
+        assert!(xs.iter().all(|x| x.dropped.contains(&"~e".to_owned())));
+
+        // wishes take precedence over optimization statements of the program
+        let lp = "a;b. c;d :- b. e. :~ b. [1@2] % @P";
+        let nav = Navigator::new(lp, vec!["0".to_string()])?;
+        let mut anav = Navigation::And(nav);
+        let xs = enumeration::closest(&mut anav, ["b", "c"].iter(), 0)?;
+        assert_eq!(xs.len(), 1);
+        assert!(xs[0].dropped.is_empty());
+
+        let lp = "a;b. p(1). :~ b, p(P). [1@P]";
+        let nav = Navigator::new(lp, vec!["0".to_string()])?;
+        let mut anav = Navigation::And(nav);
+        assert!(enumeration::closest(&mut anav, ["b"].iter(), 0).is_err());
+
+        // priorities are neither read from strings nor from scripts
+        let lp = "s(\"@x %\\\"@y\"). #script (lua) x = 1 @ 5 #end. :~ s(_). [1@2]";
+        assert_eq!(enumeration::priority(lp)?, 3);
+        assert_eq!(enumeration::priority("a :- b(@f(1)). :~ b. [1@-4]")?, 1);
+        // nor from included files
+        assert_eq!(enumeration::priority("#include \"x.lp\". :~ b. [1@2]")?, 3);
+
+        // stray disjuncts are ignored in conjunctive navigation
+        let nav = Navigator::new("a;b. c;d :- b. e.", vec!["0".to_string()])?;
+        let mut anav = Navigation::And(nav);
+        anav.delta(["&", "a"].iter());
+        anav.delta(["|", "b"].iter());
+        let xs = enumeration::closest(&mut anav, ["b"].iter(), 0)?;
+        assert_eq!(xs.len(), 1);
+        assert_eq!(xs[0].dropped, vec!["b".to_owned()]);
+
+        let nav = Navigator::new("a;b. c;d :- b. e.", vec!["0".to_string()])?;
+        let mut onav = Navigation::Or(nav);
+        onav.delta(["|", "a"].iter());
+        onav.delta(["|", "c"].iter());
+        let xs = enumeration::closest(&mut onav, ["d"].iter(), 0)?;
+        assert_eq!(xs.len(), 2);
+        assert!(xs.iter().all(|x| x.dropped == vec!["d".to_owned()]));
+
+        Ok(())
+    }
+
+    #[test]
+    fn implications_health() -> Result<()> {
+        let nav = Navigator::new("a;b. c;d :- b. e. f :- c.", vec!["0".to_string()])?;
+        let mut anav = Navigation::And(nav);
+        let g = implications::implications(&mut anav)?;
+        let classes = g.classes.iter().map(|c| c.join(",")).collect::<Vec<_>>();
+        assert_eq!(classes, vec!["a", "b", "c,f", "d"]);
+        assert_eq!(g.implies, vec![(2, 1), (3, 1)]);
+        assert_eq!(g.excludes, vec![(0, 1), (0, 2), (0, 3), (2, 3)]);
+        assert!(g.to_dot().contains("2 [label=\"c, f\"];"));
+
+        Ok(())
+    }
+
+    #[test]
+    fn co_occurrences_health() -> Result<()> {
+        let nav = Navigator::new("a;b. c;d :- b. e.", vec!["0".to_string()])?;
+        let mut anav = Navigation::And(nav);
+        let enumerated = probabilities::co_occurrences(&mut anav, 8)?;
+        assert_eq!(enumerated, probabilities::co_occurrences(&mut anav, 0)?);
+        assert_eq!(enumerated.len(), 6);
+        let bc = &enumerated[3];
+        assert_eq!(bc.facets, ("b".to_owned(), "c".to_owned()));
+        assert_eq!((bc.both, bc.either, bc.neither), (1, 2, 1));
+        assert_eq!(bc.jaccard(), 0.5);
+        assert_eq!(bc.lift(), Some(1.5));
+
+        let nav = Navigator::new("a;b. c;d :- b. e.", vec!["0".to_string()])?;
+        let mut aonav = Navigation::AndOr(nav);
+        aonav.delta(["&", "a"].iter());
+        aonav.delta(["|", "d"].iter());
+        aonav.update()?;
+        let enumerated = probabilities::co_occurrences(&mut aonav, 8)?;
+        assert_eq!(enumerated, probabilities::co_occurrences(&mut aonav, 0)?);
+        assert_eq!(enumerated.len(), 3);
+        assert!(enumerated.iter().all(|c| c.total == 2));
+
+        Ok(())
+    }
+
+    #[test]
+    fn minimize_health() -> Result<()> {
+        let nav = Navigator::new("a;b. c;d :- b. e.", vec!["0".to_string()])?;
+        let mut anav = Navigation::And(nav);
+        for f in ["e", "c", "b", "~a", "c"] {
+            anav.delta(["&", f].iter());
+        }
+        let m = routes::minimize(&mut anav, true)?;
+        assert_eq!(m.route, vec!["c".to_owned()]);
+        assert_eq!(m.redundant.len(), 4);
+        assert_eq!(anav.context(), " & c");
+
+        let nav = Navigator::new("a;b. c;d :- b. e.", vec!["0".to_string()])?;
+        let mut anav = Navigation::And(nav);
+        anav.delta(["&", "a"].iter());
+        anav.delta(["&", "~a"].iter());
+        let m = routes::minimize(&mut anav, true)?;
+        assert_eq!(m.route, vec!["a".to_owned(), "~a".to_owned()]);
+        assert!(m.redundant.is_empty());
+        assert_eq!(anav.context(), " & a & ~a");
+        assert_eq!(
+            answer_sets(&mut anav, 0, std::iter::empty::<String>())?.len(),
+            1
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn normalize_health() {
+        let n = routes::Normal::parse(" & c & a | d | a & ~a | ~d");
+        assert_eq!(n.to_string(), " & a & ~a & c | a | d | ~d");
+        assert!(n.is_contradictory() && n.is_tautological());
+        assert_eq!(n, routes::Normal::parse(" | ~d & c | a & a & ~a | d"));
+        assert!(!routes::Normal::parse(" & a | b").is_contradictory());
+    }
+
+    #[test]
+    fn route_health() -> Result<()> {
+        use routes::Route;
+
+        let route = "b & ~a ∨ ¬c".parse::<Route>()?;
+        assert_eq!(route.to_string(), "& b & ~a | ~c");
+        assert_eq!(format!("{route:#}"), "∧ b ∧ ¬a ∨ ¬c");
+        assert_eq!(route.conjuncts().count(), 2);
+        assert_eq!(
+            serde_json::to_string(&route).ok(),
+            Some("\"& b & ~a | ~c\"".to_owned())
+        );
+        assert!("& b &".parse::<Route>().is_err());
+
+        let nav = Navigator::new("a;b. c;d :- b. e.", vec!["0".to_string()])?;
+        let mut anav = Navigation::And(nav);
+        assert!(anav.activate(&"& b & x".parse()?).is_err());
+        assert!(anav.route().is_empty());
+        let route = "& b & ~c".parse::<Route>()?;
+        anav.activate(&route)?;
+        assert_eq!(anav.route(), route);
+        assert_eq!(
+            answer_sets(&mut anav, 0, std::iter::empty::<String>())?.len(),
+            1
+        );
+        anav.clear()?;
+        assert_eq!(anav.answer_sets(0, route.conjuncts())?.len(), 1);
+
+        // invalid input leaves route untouched
+        anav.delta(["x", "a"].iter());
+        anav.delta(["&", "~"].iter());
+        anav.delta(["&", "x"].iter());
+        assert!(anav.route().is_empty());
+        anav.delta(["&", "¬c"].iter());
+        assert_eq!(anav.context(), " & ~c");
+        assert_eq!(anav.route().to_string().parse::<Route>()?, anav.route());
+
+        let nav = Navigator::new("a;b. c;d :- b. e.", vec!["0".to_string()])?;
+        let mut onav = Navigation::Or(nav);
+        assert!(onav.activate(&route).is_err());
+        onav.activate(&"| a | c".parse()?)?;
+        assert_eq!(onav.answer_sets(0, std::iter::empty::<String>())?.len(), 2);
+
+        Ok(())
+    }
+
+    #[test]
+    fn switch_health() -> Result<()> {
+        let count = |nav: &mut Navigation| {
+            answer_sets(nav, 0, std::iter::empty::<String>()).map(|xs| xs.len())
+        };
+
+        let nav = Navigator::new("a;b. c;d :- b. e.", vec!["0".to_string()])?;
+        let mut anav = Navigation::And(nav);
+        anav.delta(["&", "c"].iter());
+        assert!(anav.switch(Kind::Or, true)?.is_empty());
+        anav.delta(["|", "a"].iter());
+        assert_eq!(anav.context(), " | c | a");
+        assert_eq!(count(&mut anav)?, 2);
+
+        assert!(anav.switch(Kind::And, true).is_err());
+        assert_eq!(anav.kind(), Kind::Or);
+        assert_eq!(anav.context(), " | c | a");
+        // a disjunction is a conjunctive-disjunctive route of its first disjunct
+        assert!(anav.switch(Kind::AndOr, true)?.is_empty());
+        assert_eq!(anav.context(), " & c | a");
+        assert_eq!(count(&mut anav)?, 2);
+
+        anav.clear()?;
+        anav.delta(["&", "b"].iter());
+        anav.delta(["|", "a"].iter());
+        assert_eq!(count(&mut anav)?, 3);
+        assert!(anav.switch(Kind::Or, true)?.is_empty());
+        assert_eq!(anav.context(), " | b | a");
+        assert_eq!(count(&mut anav)?, 3);
+
+        // conjunctions of several facets are no disjunctions
+        anav.switch(Kind::AndOr, true)?;
+        anav.delta(["&", "c"].iter());
+        assert_eq!(count(&mut anav)?, 2);
+        assert_eq!(
+            anav.switch(Kind::Or, false)?,
+            vec!["b".to_owned(), "c".to_owned()]
+        );
+        assert_eq!(anav.context(), " | a");
+        assert_eq!(count(&mut anav)?, 1);
+
+        // disjuncts are ignored in conjunctive navigation, so they do not become part of the route
+        assert!(anav.switch(Kind::And, true)?.is_empty());
+        anav.delta(["|", "d"].iter());
+        assert_eq!(anav.context(), " & a");
+        assert!(anav.switch(Kind::Or, true)?.is_empty());
+        assert_eq!(anav.context(), " | a");
+        assert_eq!(count(&mut anav)?, 1);
+
+        // without conjuncts, conjunctive-disjunctive routes hold regardless of their disjuncts
+        anav.switch(Kind::AndOr, true)?;
+        anav.clear()?;
+        anav.delta(["|", "a"].iter());
+        assert_eq!(count(&mut anav)?, 3);
+        assert!(anav.switch(Kind::Or, true)?.is_empty());
+        assert!(anav.context().is_empty());
+        assert_eq!(count(&mut anav)?, 3);
+
+        Ok(())
+    }
+
     /*
     #[test]
     fn or_clear() -> Result<()> {
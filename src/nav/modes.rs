@@ -1,261 +1,551 @@
-use super::{answer_set_count, utils::ToHashSet};
+use super::limits::Interruption;
+use super::ordering::Order;
+use super::{answer_set_count, collect_answer_sets, parse, utils::ToHashSet};
 use crate::lex;
-use crate::nav::weighted_navigation::Weight;
+use crate::nav::weighted_navigation::{weighted_model_count, Weight};
 use crate::nav::Navigator;
 use clingo::{SolverLiteral, Symbol};
+use rand::{Rng, SeedableRng};
+use rand_chacha::ChaCha8Rng;
 
 use super::faceted_navigation::{consequences, Consequences};
 
-#[cfg(feature = "verbose")]
-use std::time::Instant;
+use std::collections::{HashMap, HashSet};
+use std::time::{Duration, Instant};
+
+/// Returns how each facet under current route splits answer sets or facets according to
+/// `weight`, most balanced split first.
+#[allow(unused)]
+pub fn splits(nav: &mut Navigator, weight: Weight) -> Option<Vec<Split>> {
+    InformationGain::new(weight).splits(nav)
+}
+
+/// Number of answer sets sampled to order candidates of a budgeted step.
+#[allow(unused)]
+const SAMPLE: usize = 16;
 
 #[allow(unused)]
 pub enum Mode {
-    GoalOriented,
+    /// Picks facets leading towards answer sets compatible with goal.
+    GoalOriented(Goal),
+    /// Picks facets of maximal weight, i.e., facets minimally reducing the weight of the route.
     MinWeighted(Weight),
+    /// Picks facets of minimal weight, i.e., facets maximally reducing the weight of the route.
     MaxWeighted(Weight),
+    /// Picks facets of maximal score, i.e., facets minimally reducing the score of the route,
+    /// named after [`Mode::MinWeighted`].
+    MinScored(Box<dyn Scoring>),
+    /// Picks facets of minimal score, i.e., facets maximally reducing the score of the route,
+    /// named after [`Mode::MaxWeighted`].
+    MaxScored(Box<dyn Scoring>),
+    /// Picks facets splitting answer sets or facets most evenly.
+    Balanced(InformationGain),
+    /// Picks first facet of best sequence of facets.
+    Lookahead(Lookahead),
+    /// Picks facets uniformly at random.
+    Random(Random),
 }
-pub trait Guide {
-    fn step(
+impl Mode {
+    /// Returns scoring of mode and whether scores are minimized.
+    #[allow(unused)]
+    fn scoring(&mut self) -> (&mut dyn Scoring, bool) {
+        match self {
+            Self::GoalOriented(g) => (g, true),
+            Self::MinWeighted(w) => (w, false),
+            Self::MaxWeighted(w) => (w, true),
+            Self::MinScored(s) => (s.as_mut(), false),
+            Self::MaxScored(s) => (s.as_mut(), true),
+            Self::Balanced(s) => (s, false),
+            Self::Lookahead(s) => {
+                let minimize = s.minimize;
+                (s, minimize)
+            }
+            Self::Random(s) => (s, false),
+        }
+    }
+}
+
+/// Scores candidate facets, i.e., facets and their negations, where lower scores mean more
+/// restrictive candidates.
+#[allow(unused)]
+pub trait Scoring {
+    /// Returns score of activating `facet`, which is either `f` or `~f`, where `route` is the
+    /// current route extended by `facet`. Scores above `bound` need not be exact and candidates
+    /// of infinite score are discarded.
+    fn score(
         &mut self,
         nav: &mut Navigator,
-        split_on: &mut Option<usize>,
-    ) -> Option<(String, SolverLiteral)>;
-    fn step_wrt(
+        facet: &str,
+        route: &[SolverLiteral],
+        bound: Option<f64>,
+    ) -> Option<f64>;
+    /// Returns score no candidate among `facets` facets can improve upon, given score `total` of
+    /// the current route, if known.
+    fn optimum(&self, _minimize: bool, _facets: usize, _total: Option<f64>) -> Option<f64> {
+        None
+    }
+    /// Returns whether scores of `f` and `~f` add up to the score of the current route.
+    fn complements(&self) -> bool {
+        false
+    }
+}
+impl Scoring for Weight {
+    fn score(
         &mut self,
         nav: &mut Navigator,
-        curr: &[String],
-        split_on: &mut Option<usize>,
-    ) -> Option<(String, SolverLiteral)>;
+        _facet: &str,
+        route: &[SolverLiteral],
+        bound: Option<f64>,
+    ) -> Option<f64> {
+        let count = match self {
+            Self::AnswerSetCounting => {
+                answer_set_count(nav, route, bound.map_or(0, |b| b as usize)).ok()?
+            }
+            Self::FacetCounting => current_facets(nav, route)?.len(),
+            Self::BcCounting => consequences(Consequences::Brave, nav, route)?.len(),
+            Self::CcCounting => consequences(Consequences::Cautious, nav, route)?.len(),
+            Self::WeightedModelCounting(weights) => {
+                return weighted_model_count(nav, route, weights, &[])
+                    .ok()
+                    .map(|(wmc, _)| wmc)
+            }
+        };
+
+        Some(count as f64)
+    }
+
+    fn optimum(&self, minimize: bool, facets: usize, total: Option<f64>) -> Option<f64> {
+        match (self, minimize) {
+            (Self::AnswerSetCounting, true) => Some(1.0),
+            (Self::AnswerSetCounting, _) => total.map(|t| t - 1.0),
+            (Self::FacetCounting, true) => Some(0.0),
+            (Self::FacetCounting, _) => Some(facets as f64 - 1.0),
+            _ => None,
+        }
+    }
+
+    fn complements(&self) -> bool {
+        matches!(self, Self::AnswerSetCounting)
+    }
 }
-impl Guide for Mode {
-    fn step(
+
+/// Split of answer sets or facets by a facet.
+#[allow(unused)]
+#[derive(Debug, Clone)]
+pub struct Split {
+    /// Facet.
+    pub facet: String,
+    /// Weight of current route extended by facet.
+    pub positive: f64,
+    /// Weight of current route extended by negated facet.
+    pub negative: f64,
+}
+impl Split {
+    /// Returns share of weight under facet.
+    #[allow(unused)]
+    pub fn ratio(&self) -> f64 {
+        let total = self.positive + self.negative;
+        match total > 0.0 {
+            true => self.positive / total,
+            _ => 0.0,
+        }
+    }
+
+    /// Returns entropy of split in bits, i.e., expected information gained by asking for facet.
+    #[allow(unused)]
+    pub fn entropy(&self) -> f64 {
+        [self.ratio(), 1.0 - self.ratio()]
+            .iter()
+            .filter(|p| **p > 0.0)
+            .map(|p| -p * p.log2())
+            .sum()
+    }
+}
+
+/// Scores candidates by entropy of the split they induce, caching splits of current route.
+#[derive(Debug, Clone)]
+pub struct InformationGain {
+    weight: Weight,
+    /// Route splits are cached for.
+    route: Vec<SolverLiteral>,
+    /// Weight of `route`.
+    total: Option<f64>,
+    /// Splits under `route` by facet.
+    cache: HashMap<String, Split>,
+}
+impl InformationGain {
+    #[allow(unused)]
+    pub fn new(weight: Weight) -> Self {
+        Self {
+            weight,
+            route: vec![],
+            total: None,
+            cache: HashMap::new(),
+        }
+    }
+
+    /// Returns split by `facet`, which is either `f` or `~f`, where `route` is the current route
+    /// extended by `facet`.
+    #[allow(unused)]
+    pub fn split(
         &mut self,
         nav: &mut Navigator,
-        split_on: &mut Option<usize>,
-    ) -> Option<(String, SolverLiteral)> {
-        let mut active = nav.conjuncts.0.clone();
-        let bc = consequences(Consequences::Brave, nav, &active)?;
-        let fs = match !bc.is_empty() {
-            true => unsafe {
-                consequences(Consequences::Cautious, nav, &active)
-                    .as_ref()
-                    .map(|ccs| {
-                        bc.difference_as_set(&ccs)
-                            .iter()
-                            .cloned()
-                            .collect::<Vec<Symbol>>()
-                    })
-                    .unwrap_unchecked()
-            },
-            _ => bc,
+        facet: &str,
+        route: &[SolverLiteral],
+    ) -> Option<Split> {
+        let (l, prefix) = route.split_last()?;
+        let (f, l) = match facet.strip_prefix('~') {
+            Some(f) => (f, l.negate()),
+            _ => (facet, *l),
         };
-        let lits = nav.literals.clone();
-        if fs.is_empty() {
-            return None;
+        if self.route != prefix {
+            self.route = prefix.to_vec();
+            self.total = None;
+            self.cache.clear();
+        }
+        if let Some(split) = self.cache.get(f) {
+            return Some(split.clone());
         }
 
-        match self {
-            Self::GoalOriented => fs
-                .into_iter()
-                .next()
-                .and_then(|f| Some((f.to_string(), *unsafe { lits.get(&f).unwrap_unchecked() }))),
-            Self::MaxWeighted(Weight::FacetCounting) => {
-                let (mut curr, mut f): (usize, Option<(String, SolverLiteral)>) =
-                    (fs.len() - 1, None);
-                for sym in fs {
-                    let l = unsafe { lits.get(&sym).unwrap_unchecked() };
-                    active.push(*l);
-                    let bc = consequences(Consequences::Brave, nav, &active)?;
-                    let cc = consequences(Consequences::Cautious, nav, &active)?;
-                    let count = bc.to_hashset().difference(&cc.to_hashset()).count();
-                    if count == 0 {
-                        return Some((sym.to_string(), *l));
-                    }
-                    if count <= curr {
-                        curr = count;
-                        f = Some((sym.to_string(), *l));
-                    }
-                    active.pop();
+        let mut active = prefix.to_vec();
+        active.push(l);
+        let positive = self.weight.score(nav, f, &active, None)?;
+        let negative = match self.weight.complements() {
+            true => {
+                let total = match self.total {
+                    Some(total) => total,
+                    _ => self.weight.score(nav, "", prefix, None)?,
+                };
+                self.total = Some(total);
+                (total - positive).max(0.0)
+            }
+            _ => {
+                active.pop();
+                active.push(l.negate());
+                self.weight.score(nav, f, &active, None)?
+            }
+        };
 
-                    let ln = l.negate();
-                    active.push(ln);
-                    let bc = consequences(Consequences::Brave, nav, &active)?;
-                    let cc = consequences(Consequences::Cautious, nav, &active)?;
-                    let count = bc.to_hashset().difference(&cc.to_hashset()).count();
-                    if count == 0 {
-                        return Some((format!("~{sym}"), ln));
-                    }
-                    if count <= curr {
-                        curr = count;
-                        f = Some((format!("~{sym}"), ln));
-                    }
-                    active.pop();
-                }
+        let split = Split {
+            facet: f.to_owned(),
+            positive,
+            negative,
+        };
+        self.cache.insert(f.to_owned(), split.clone());
 
-                f
-            }
-            Self::MaxWeighted(Weight::AnswerSetCounting) => {
-                let (mut curr, mut f): (usize, Option<(String, SolverLiteral)>) =
-                    (usize::MAX - 1, None);
-
-                if let Some(c) = split_on {
-                    for sym in fs {
-                        let l = unsafe { lits.get(&sym).unwrap_unchecked() };
-                        active.push(*l);
-                        let count = answer_set_count(nav, &active, curr).ok()?;
-                        if count == 1 {
-                            return Some((sym.to_string(), *l));
-                        }
-                        if count <= curr {
-                            curr = count;
-                            f = Some((sym.to_string(), *l));
-                        }
-                        active.pop();
+        Some(split)
+    }
 
-                        let ln = l.negate();
-                        let count_ = *c - count;
-                        if count_ == 1 {
-                            return Some((format!("~{sym}"), ln));
-                        }
-                        if count_ <= curr {
-                            curr = count_;
-                            f = Some((format!("~{sym}"), ln));
-                        }
-                    }
-                } else {
-                    for sym in fs {
-                        let l = unsafe { lits.get(&sym).unwrap_unchecked() };
-                        active.push(*l);
-                        let count = answer_set_count(nav, &active, curr).ok()?;
-                        if count == 1 {
-                            return Some((sym.to_string(), *l));
-                        }
-                        if count <= curr {
-                            curr = count;
-                            f = Some((sym.to_string(), *l));
-                        }
-                        active.pop();
+    /// Returns splits by all facets under current route, most balanced first.
+    #[allow(unused)]
+    pub fn splits(&mut self, nav: &mut Navigator) -> Option<Vec<Split>> {
+        let mut active = nav.conjuncts.0.clone();
+        let mut splits = vec![];
+        for f in current_facets(nav, &active)? {
+            let Some(l) = nav.literals.get(&f).copied() else {
+                continue;
+            };
+            active.push(l);
+            splits.push(self.split(nav, &f.to_string(), &active)?);
+            active.pop();
+        }
+        splits.sort_by(|a, b| b.entropy().total_cmp(&a.entropy()));
 
-                        let ln = l.negate();
-                        active.push(ln);
-                        let count = answer_set_count(nav, &active, curr).ok()?;
-                        if count == 1 {
-                            return Some((format!("~{sym}"), ln));
-                        }
-                        if count <= curr {
-                            curr = count;
-                            f = Some((format!("~{sym}"), ln));
-                        }
-                        active.pop();
-                    }
-                }
+        Some(splits)
+    }
+}
+impl Scoring for InformationGain {
+    fn score(
+        &mut self,
+        nav: &mut Navigator,
+        facet: &str,
+        route: &[SolverLiteral],
+        _bound: Option<f64>,
+    ) -> Option<f64> {
+        self.split(nav, facet, route).map(|s| s.entropy())
+    }
 
-                *split_on = Some(curr);
+    fn optimum(&self, _minimize: bool, _facets: usize, _total: Option<f64>) -> Option<f64> {
+        Some(1.0)
+    }
+}
 
-                f
-            }
-            Self::MinWeighted(Weight::FacetCounting) => {
-                let ub = fs.len() - 1;
-                let (mut curr, mut f): (usize, Option<(String, SolverLiteral)>) = (0, None);
-                for sym in fs {
-                    let l = unsafe { lits.get(&sym).unwrap_unchecked() };
-
-                    let ln = l.negate();
-                    active.push(ln);
-                    let bc = consequences(Consequences::Brave, nav, &active)?;
-                    let cc = consequences(Consequences::Cautious, nav, &active)?;
-                    let count = bc.to_hashset().difference(&cc.to_hashset()).count();
-                    if count == ub {
-                        return Some((format!("~{sym}"), ln));
-                    }
-                    if count >= curr {
-                        curr = count;
-                        f = Some((format!("~{sym}"), ln));
-                    }
-                    active.pop();
+/// Scores candidates by best weight reachable within `depth` steps, keeping `beam` best routes
+/// per step.
+#[derive(Debug, Clone)]
+pub struct Lookahead {
+    weight: Weight,
+    depth: usize,
+    beam: usize,
+    /// Whether weights are minimized, i.e., `MaxWeighted` semantics.
+    minimize: bool,
+}
+impl Lookahead {
+    /// Minimizes `weight` over `depth` steps, keeping `beam` best routes per step.
+    #[allow(unused)]
+    pub fn new(weight: Weight, depth: usize, beam: usize) -> Self {
+        Self {
+            weight,
+            depth: depth.max(1),
+            beam: beam.max(1),
+            minimize: true,
+        }
+    }
 
-                    active.push(*l);
-                    let bc = consequences(Consequences::Brave, nav, &active)?;
-                    let cc = consequences(Consequences::Cautious, nav, &active)?;
-                    let count = bc.to_hashset().difference(&cc.to_hashset()).count();
-                    if count == ub {
-                        return Some((sym.to_string(), *l));
-                    }
+    /// Maximizes weight instead, i.e., `MinWeighted` semantics.
+    #[allow(unused)]
+    pub fn maximize(mut self) -> Self {
+        self.minimize = false;
+        self
+    }
+
+    /// Returns best sequence of at most `depth` facets under current route and its weight.
+    #[allow(unused)]
+    pub fn sequence(&mut self, nav: &mut Navigator) -> Option<(Vec<String>, f64)> {
+        let route = nav.conjuncts.0.clone();
+        self.search(nav, &route, self.depth)
+            .map(|(score, fs)| (fs, score))
+    }
 
-                    if count >= curr {
-                        curr = count;
-                        f = Some((sym.to_string(), *l));
+    /// Returns best weight reachable from `route` within `depth` steps and facets leading there.
+    fn search(
+        &mut self,
+        nav: &mut Navigator,
+        route: &[SolverLiteral],
+        depth: usize,
+    ) -> Option<(f64, Vec<String>)> {
+        let score = self.weight.score(nav, "", route, None)?;
+        let mut frontier = vec![(score, route.to_vec(), vec![])];
+        let mut finals = vec![];
+
+        for _ in 0..depth {
+            let mut next = vec![];
+            let mut seen = HashSet::new();
+            for (score, r, fs) in frontier {
+                let facets = current_facets(nav, &r)?;
+                if facets.is_empty() {
+                    finals.push((score, r, fs));
+                    continue;
+                }
+                for f in facets {
+                    let Some(l) = nav.literals.get(&f).copied() else {
+                        continue;
+                    };
+                    for (repr, l) in [(f.to_string(), l), (format!("~{f}"), l.negate())] {
+                        let mut fs = fs.clone();
+                        fs.push(repr.clone());
+                        // sequences activating same facets lead to same route
+                        let mut key = fs.clone();
+                        key.sort();
+                        if !seen.insert(key) {
+                            continue;
+                        }
+                        let mut r = r.clone();
+                        r.push(l);
+                        let score = self.weight.score(nav, &repr, &r, None)?;
+                        next.push((score, r, fs));
                     }
-                    active.pop();
                 }
-
-                f
             }
-            Self::MinWeighted(Weight::AnswerSetCounting) => {
-                let ub = usize::MAX - 1;
-                let (mut curr, mut f): (usize, Option<(String, SolverLiteral)>) = (1, None);
-
-                if let Some(c) = split_on {
-                    for sym in fs {
-                        let l = unsafe { lits.get(&sym).unwrap_unchecked() };
-                        let ln = l.negate();
-
-                        active.push(ln);
-                        let count = answer_set_count(nav, &active, curr).ok()?;
-                        if count == ub {
-                            return Some((sym.to_string(), *l));
-                        }
-                        if count >= curr {
-                            curr = count;
-                            f = Some((format!("~{sym}"), ln));
-                        }
-                        active.pop();
+            self.rank(&mut next);
+            next.truncate(self.beam);
+            frontier = next;
+        }
+        finals.extend(frontier);
+        self.rank(&mut finals);
 
-                        let count_ = *c - count;
-                        if count_ == ub {
-                            return Some((sym.to_string(), *l));
-                        }
-                        if count_ >= curr {
-                            curr = count_;
-                            f = Some((sym.to_string(), *l));
-                        }
-                    }
-                } else {
-                    for sym in fs {
-                        let l = unsafe { lits.get(&sym).unwrap_unchecked() };
-                        let ln = l.negate();
-
-                        active.push(ln);
-                        let count = answer_set_count(nav, &active, curr).ok()?;
-                        if count == ub {
-                            return Some((sym.to_string(), *l));
-                        }
-                        if count >= curr {
-                            curr = count;
-                            f = Some((format!("~{sym}"), ln));
-                        }
-                        active.pop();
+        finals.into_iter().next().map(|(score, _, fs)| (score, fs))
+    }
 
-                        active.push(*l);
-                        let count = answer_set_count(nav, &active, curr).ok()?;
-                        if count == ub {
-                            return Some((sym.to_string(), *l));
-                        }
-                        if count >= curr {
-                            curr = count;
-                            f = Some((sym.to_string(), *l));
-                        }
-                        active.pop();
-                    }
+    /// Sorts routes best first.
+    fn rank<T>(&self, routes: &mut [(f64, T, Vec<String>)]) {
+        routes.sort_by(|a, b| match self.minimize {
+            true => a.0.total_cmp(&b.0),
+            _ => b.0.total_cmp(&a.0),
+        })
+    }
+}
+impl Scoring for Lookahead {
+    fn score(
+        &mut self,
+        nav: &mut Navigator,
+        _facet: &str,
+        route: &[SolverLiteral],
+        _bound: Option<f64>,
+    ) -> Option<f64> {
+        self.search(nav, route, self.depth - 1)
+            .map(|(score, _)| score)
+    }
+
+    fn optimum(&self, minimize: bool, facets: usize, total: Option<f64>) -> Option<f64> {
+        self.weight.optimum(minimize, facets, total)
+    }
+}
+
+/// Desired facets, i.e., partial assignment answer sets should comply with.
+///
+/// Scores candidates that keep goal satisfiable by number of goal facets not yet entailed, breaking
+/// ties by the share of remaining facets. Candidates ruling out the goal score infinite.
+#[derive(Debug, Clone, Default)]
+pub struct Goal(Vec<(Symbol, bool)>);
+impl Goal {
+    /// Goal of facets, which are negated if prefixed by `~`.
+    #[allow(unused)]
+    pub fn new<S: ToString>(goal: impl Iterator<Item = S>) -> Self {
+        Self(
+            goal.filter_map(|f| {
+                let s = f.to_string();
+                match s.strip_prefix('~') {
+                    Some(a) => parse(a).map(|sym| (sym, false)),
+                    _ => parse(&s).map(|sym| (sym, true)),
                 }
+            })
+            .collect(),
+        )
+    }
 
-                *split_on = Some(curr);
+    /// Goal of partial assignment mapping atoms to truth values.
+    #[allow(unused)]
+    pub fn assignment<S: ToString>(assignment: impl Iterator<Item = (S, bool)>) -> Self {
+        Self(
+            assignment
+                .filter_map(|(a, v)| parse(&a.to_string()).map(|sym| (sym, v)))
+                .collect(),
+        )
+    }
 
-                f
-            }
-            _ => todo!(),
+    /// Returns literals of goal, ignoring unknown atoms.
+    fn literals(&self, nav: &Navigator) -> Vec<SolverLiteral> {
+        self.0
+            .iter()
+            .filter_map(|(sym, v)| {
+                nav.literals.get(sym).map(|l| match v {
+                    true => *l,
+                    _ => l.negate(),
+                })
+            })
+            .collect()
+    }
+}
+impl Scoring for Goal {
+    fn score(
+        &mut self,
+        nav: &mut Navigator,
+        _facet: &str,
+        route: &[SolverLiteral],
+        _bound: Option<f64>,
+    ) -> Option<f64> {
+        let mut goal = route.to_vec();
+        goal.extend(self.literals(nav));
+        if answer_set_count(nav, &goal, 1).ok()? == 0 {
+            return Some(f64::INFINITY);
+        }
+
+        let bc = consequences(Consequences::Brave, nav, route)?;
+        let cc = consequences(Consequences::Cautious, nav, route)?;
+        let open = self
+            .0
+            .iter()
+            .filter(|(sym, v)| match v {
+                true => !cc.contains(sym),
+                _ => bc.contains(sym),
+            })
+            .count();
+        let fc = bc.len().saturating_sub(cc.len()) as f64;
+
+        Some(open as f64 + fc / (fc + 1.0))
+    }
+
+    fn optimum(&self, _minimize: bool, _facets: usize, _total: Option<f64>) -> Option<f64> {
+        Some(0.0)
+    }
+}
+
+/// Random baseline, picking each candidate with equal probability.
+///
+/// Scores candidates by independent uniform draws, so the candidate of maximal score is uniformly
+/// distributed. Draws are reproducible for a fixed seed on any platform, as the generator, unlike
+/// `StdRng`, does not change with `rand` releases.
+#[derive(Debug, Clone)]
+pub struct Random(ChaCha8Rng);
+impl Random {
+    #[allow(unused)]
+    pub fn new(seed: u64) -> Self {
+        Self(ChaCha8Rng::seed_from_u64(seed))
+    }
+}
+impl Scoring for Random {
+    fn score(
+        &mut self,
+        _nav: &mut Navigator,
+        _facet: &str,
+        _route: &[SolverLiteral],
+        _bound: Option<f64>,
+    ) -> Option<f64> {
+        Some(self.0.gen())
+    }
+}
+
+/// Outcome of a budgeted step.
+#[allow(unused)]
+#[derive(Debug, Clone)]
+pub struct Anytime {
+    /// Best facet found before budget expired.
+    pub facet: Option<(String, SolverLiteral)>,
+    /// Score of `facet`, unless no candidate was scored in time.
+    pub score: Option<f64>,
+    /// Number of candidates scored.
+    pub scored: usize,
+    /// Number of candidates, i.e., facets and their negations.
+    pub candidates: usize,
+}
+
+pub trait Guide {
+    #[allow(unused)]
+    fn step(
+        &mut self,
+        nav: &mut Navigator,
+        split_on: &mut Option<usize>,
+    ) -> Option<(String, SolverLiteral)> {
+        self.step_scored(nav, split_on).map(|(f, _)| f)
+    }
+    /// Returns next facet along with its score.
+    fn step_scored(
+        &mut self,
+        nav: &mut Navigator,
+        split_on: &mut Option<usize>,
+    ) -> Option<((String, SolverLiteral), f64)>;
+    fn step_wrt(
+        &mut self,
+        nav: &mut Navigator,
+        curr: &[String],
+        split_on: &mut Option<usize>,
+    ) -> Option<(String, SolverLiteral)>;
+    /// Returns best facet found within `budget`, scoring candidates in heuristic order.
+    fn step_within(&mut self, nav: &mut Navigator, budget: Duration) -> Option<Anytime>;
+    /// Returns all facets and their negations with their scores, best first and ties broken by
+    /// facet.
+    #[allow(unused)]
+    fn rank(&mut self, nav: &mut Navigator) -> Option<Vec<(String, f64)>>;
+}
+impl Guide for Mode {
+    fn step_scored(
+        &mut self,
+        nav: &mut Navigator,
+        split_on: &mut Option<usize>,
+    ) -> Option<((String, SolverLiteral), f64)> {
+        let active = nav.conjuncts.0.clone();
+        let fs = current_facets(nav, &active)?;
+        let facets = fs
+            .into_iter()
+            .filter_map(|f| nav.literals.get(&f).map(|l| (f.to_string(), *l)))
+            .collect::<Vec<_>>();
+        if facets.is_empty() {
+            return None;
         }
+
+        let (scoring, minimize) = self.scoring();
+        guide(scoring, minimize, nav, facets, split_on)
     }
 
     fn step_wrt(
@@ -264,10 +554,15 @@ impl Guide for Mode {
         curr: &[String],
         split_on: &mut Option<usize>,
     ) -> Option<(String, SolverLiteral)> {
-        let mut active = nav.conjuncts.0.clone();
-        let fs = curr;
-        let lits = nav.literals.clone();
-        if fs.is_empty() {
+        let facets = curr
+            .iter()
+            .filter_map(|f| {
+                lex::parse(f)
+                    .and_then(|sym| nav.literals.get(&sym))
+                    .map(|l| (f.clone(), *l))
+            })
+            .collect::<Vec<_>>();
+        if facets.is_empty() {
             return None;
         }
 
@@ -275,299 +570,252 @@ impl Guide for Mode {
         eprintln!("step started");
         #[cfg(feature = "verbose")]
         let start = Instant::now();
-        let ret = match self {
-            Self::GoalOriented => fs.into_iter().next().and_then(|f| {
-                Some((f.clone(), *unsafe {
-                    lits.get(&lex::parse(f).unwrap_unchecked())
-                        .unwrap_unchecked()
-                }))
-            }),
-            Self::MaxWeighted(Weight::FacetCounting) => {
-                let (mut curr, mut f): (usize, Option<(String, SolverLiteral)>) =
-                    (fs.len() - 1, unsafe {
-                        let s = fs.get_unchecked(0);
-                        Some((
-                            s.to_string(),
-                            *lits
-                                .get(&lex::parse(s).unwrap_unchecked())
-                                .unwrap_unchecked(),
-                        ))
-                    });
-                for sym in fs {
-                    let l = unsafe {
-                        lits.get(&lex::parse(sym).unwrap_unchecked())
-                            .unwrap_unchecked()
-                    };
-                    active.push(*l);
-                    let bc = consequences(Consequences::Brave, nav, &active)?;
-                    let cc = consequences(Consequences::Cautious, nav, &active)?;
-                    let count = bc.to_hashset().difference(&cc.to_hashset()).count();
-                    if count == 0 {
-                        #[cfg(feature = "verbose")]
-                        println!("early stoppage +");
-                        return Some((sym.to_string(), *l));
-                    }
-                    if count <= curr {
-                        curr = count;
-                        f = Some((sym.to_string(), *l));
-                    }
-                    active.pop();
+        let (scoring, minimize) = self.scoring();
+        let ret = guide(scoring, minimize, nav, facets, split_on).map(|(f, _)| f);
 
-                    let ln = l.negate();
-                    active.push(ln);
-                    let bc = consequences(Consequences::Brave, nav, &active)?;
-                    let cc = consequences(Consequences::Cautious, nav, &active)?;
-                    let count = bc.to_hashset().difference(&cc.to_hashset()).count();
-                    if count == 0 {
-                        #[cfg(feature = "verbose")]
-                        println!("early stoppage -");
-                        return Some((format!("~{sym}"), ln));
-                    }
-                    if count <= curr {
-                        curr = count;
-                        f = Some((format!("~{sym}"), ln));
-                    }
-                    active.pop();
+        #[cfg(feature = "verbose")]
+        eprintln!("\nstep elapsed: {:?}", start.elapsed());
+        ret
+    }
 
-                    #[cfg(feature = "verbose")]
-                    eprint!(".");
-                }
+    fn step_within(&mut self, nav: &mut Navigator, budget: Duration) -> Option<Anytime> {
+        nav.check_stopped().ok()?;
+        let deadline = Instant::now() + budget;
+        let mut active = nav.conjuncts.0.clone();
+        let fs = current_facets(nav, &active)?;
+        if fs.is_empty() {
+            return None;
+        }
+        let candidates = fs.len() * 2;
 
-                f
+        let (scoring, minimize) = self.scoring();
+        let optimum = scoring.optimum(minimize, fs.len(), None);
+
+        #[cfg(feature = "verbose")]
+        eprintln!("budgeted step started");
+        let outer = nav.limit.clone();
+        nav.limit = Some(outer.clone().unwrap_or_default().tighten(deadline));
+
+        let ordered = order_candidates(nav, &active, fs, minimize);
+
+        let mut best: Option<(f64, (String, SolverLiteral))> = None;
+        let mut scored = 0;
+        for (repr, l) in ordered.iter() {
+            let bound = match minimize {
+                true => best.as_ref().map(|(s, _)| *s),
+                _ => None,
+            };
+            active.push(*l);
+            let score = scoring.score(nav, repr, &active, bound);
+            active.pop();
+            let Some(score) = score else {
+                break;
+            };
+            scored += 1;
+            if !score.is_finite() {
+                continue;
             }
-            Self::MaxWeighted(Weight::AnswerSetCounting) => {
-                let (mut curr, mut f): (usize, Option<(String, SolverLiteral)>) =
-                    (usize::MAX - 1, unsafe {
-                        let s = fs.get_unchecked(0);
-                        Some((
-                            s.to_string(),
-                            *lits
-                                .get(&lex::parse(s).unwrap_unchecked())
-                                .unwrap_unchecked(),
-                        ))
-                    });
-
-                if let Some(c) = split_on {
-                    for sym in fs {
-                        let l = unsafe {
-                            lits.get(&lex::parse(sym).unwrap_unchecked())
-                                .unwrap_unchecked()
-                        };
-                        active.push(*l);
-                        let count = answer_set_count(nav, &active, curr).ok()?;
-                        if count == 1 {
-                            #[cfg(feature = "verbose")]
-                            println!("early stoppage +");
-                            return Some((sym.to_string(), *l));
-                        }
-                        if count <= curr {
-                            curr = count;
-                            f = Some((sym.to_string(), *l));
-                        }
-                        active.pop();
-
-                        let ln = l.negate();
-                        let count_ = *c - count;
-                        if count_ == 1 {
-                            #[cfg(feature = "verbose")]
-                            println!("early stoppage -");
-                            return Some((format!("~{sym}"), ln));
-                        }
-                        if count_ <= curr {
-                            curr = count_;
-                            f = Some((format!("~{sym}"), ln));
-                        }
-                        #[cfg(feature = "verbose")]
-                        eprint!(".");
-                    }
-                } else {
-                    for sym in fs {
-                        let l = unsafe {
-                            lits.get(&lex::parse(sym).unwrap_unchecked())
-                                .unwrap_unchecked()
-                        };
-                        active.push(*l);
-                        let count = answer_set_count(nav, &active, curr).ok()?;
-                        if count == 1 {
-                            #[cfg(feature = "verbose")]
-                            println!("early stoppage +");
-                            return Some((sym.to_string(), *l));
-                        }
-                        if count <= curr {
-                            curr = count;
-                            f = Some((sym.to_string(), *l));
-                        }
-                        active.pop();
-
-                        let ln = l.negate();
-                        active.push(ln);
-                        let count = answer_set_count(nav, &active, curr).ok()?;
-                        if count == 1 {
-                            #[cfg(feature = "verbose")]
-                            println!("early stoppage -");
-                            return Some((format!("~{sym}"), ln));
-                        }
-                        if count <= curr {
-                            curr = count;
-                            f = Some((format!("~{sym}"), ln));
-                        }
-                        active.pop();
-                        #[cfg(feature = "verbose")]
-                        eprint!(".");
-                    }
-                }
 
-                *split_on = Some(curr);
+            if improves(minimize, score, &best, false) {
+                best = Some((score, (repr.clone(), *l)));
+            }
+            if optimum == Some(score) {
+                #[cfg(feature = "verbose")]
+                println!("early stoppage");
+                break;
+            }
+            #[cfg(feature = "verbose")]
+            eprint!(".");
+        }
 
-                f
+        // expiry of own budget is no error, whereas expiry of an enclosing limit is
+        if let Some((Interruption::Timeout, _)) = nav.stopped {
+            if !outer.as_ref().is_some_and(|l| l.expires_by(deadline)) {
+                nav.stopped = None;
             }
-            Self::MinWeighted(Weight::FacetCounting) => {
-                let ub = fs.len() - 1;
-                let (mut curr, mut f): (usize, Option<(String, SolverLiteral)>) = (0, unsafe {
-                    let s = fs.get_unchecked(0);
-                    Some((
-                        s.to_string(),
-                        *lits
-                            .get(&lex::parse(s).unwrap_unchecked())
-                            .unwrap_unchecked(),
-                    ))
-                });
-                for sym in fs {
-                    let l = unsafe {
-                        lits.get(&lex::parse(sym).unwrap_unchecked())
-                            .unwrap_unchecked()
-                    };
+        }
+        nav.limit = outer;
+        #[cfg(feature = "verbose")]
+        eprintln!("\nbudgeted step scored {scored}/{candidates}");
+
+        Some(match best {
+            Some((score, f)) => Anytime {
+                facet: Some(f),
+                score: Some(score),
+                scored,
+                candidates,
+            },
+            _ => Anytime {
+                facet: ordered.into_iter().next(),
+                score: None,
+                scored,
+                candidates,
+            },
+        })
+    }
 
-                    let ln = l.negate();
-                    active.push(ln);
-                    let bc = consequences(Consequences::Brave, nav, &active)?;
-                    let cc = consequences(Consequences::Cautious, nav, &active)?;
-                    let count = bc.to_hashset().difference(&cc.to_hashset()).count();
-                    if count == ub {
-                        #[cfg(feature = "verbose")]
-                        println!("early stoppage -");
-                        return Some((format!("~{sym}"), ln));
-                    }
-                    if count >= curr {
-                        curr = count;
-                        f = Some((format!("~{sym}"), ln));
-                    }
-                    active.pop();
+    fn rank(&mut self, nav: &mut Navigator) -> Option<Vec<(String, f64)>> {
+        let mut active = nav.conjuncts.0.clone();
+        let fs = current_facets(nav, &active)?;
 
-                    active.push(*l);
-                    let bc = consequences(Consequences::Brave, nav, &active)?;
-                    let cc = consequences(Consequences::Cautious, nav, &active)?;
-                    let count = bc.to_hashset().difference(&cc.to_hashset()).count();
-                    if count == ub {
-                        #[cfg(feature = "verbose")]
-                        println!("early stoppage +");
-                        return Some((sym.to_string(), *l));
-                    }
-                    if count >= curr {
-                        curr = count;
-                        f = Some((sym.to_string(), *l));
-                    }
+        #[cfg(feature = "verbose")]
+        eprintln!("ranking started");
+        #[cfg(feature = "verbose")]
+        let start = Instant::now();
+        let (scoring, minimize) = self.scoring();
+        let mut ranking = Vec::with_capacity(fs.len() * 2);
+        for f in fs {
+            let Some(l) = nav.literals.get(&f).copied() else {
+                continue;
+            };
+            let repr = f.to_string();
+            for (repr, l) in [(format!("~{repr}"), l.negate()), (repr, l)] {
+                active.push(l);
+                let score = scoring.score(nav, &repr, &active, None);
+                active.pop();
+                ranking.push((repr, score?));
+            }
+            #[cfg(feature = "verbose")]
+            eprint!(".");
+        }
+        ranking.sort_by(|(f, s), (g, t)| {
+            match minimize {
+                true => s.total_cmp(t),
+                _ => t.total_cmp(s),
+            }
+            .then_with(|| f.cmp(g))
+        });
+
+        #[cfg(feature = "verbose")]
+        eprintln!("\nranking elapsed: {:?}", start.elapsed());
+        Some(ranking)
+    }
+}
+
+/// Returns best candidate among `facets` and their negations according to `scoring`, along with
+/// its score.
+///
+/// Candidates are scored in the order of `facets`, the preferred polarity of each facet first, and
+/// ties go to the candidate scored last.
+///
+/// If `scoring` complements and `split_on` holds the score of the current route, the score of
+/// the second polarity of each facet is derived instead of computed. `split_on` is set to the
+/// score of the returned candidate afterwards.
+#[allow(unused)]
+fn guide(
+    scoring: &mut dyn Scoring,
+    minimize: bool,
+    nav: &mut Navigator,
+    facets: Vec<(String, SolverLiteral)>,
+    split_on: &mut Option<usize>,
+) -> Option<((String, SolverLiteral), f64)> {
+    let mut active = nav.conjuncts.0.clone();
+    let total = split_on.filter(|_| scoring.complements()).map(|c| c as f64);
+    let optimum = scoring.optimum(minimize, facets.len(), total);
+
+    let mut best: Option<(f64, (String, SolverLiteral))> = None;
+    'candidates: for (repr, l) in facets {
+        let mut polarities = [(repr.clone(), l), (format!("~{repr}"), l.negate())];
+        if !minimize {
+            polarities.reverse();
+        }
+
+        let mut first = None;
+        for f in polarities {
+            let score = match (first, total) {
+                (Some(s), Some(t)) => t - s,
+                _ => {
+                    // scores must be exact if they are complemented
+                    let bound = match minimize && total.is_none() {
+                        true => best.as_ref().map(|(s, _)| *s),
+                        _ => None,
+                    };
+                    active.push(f.1);
+                    let score = scoring.score(nav, &f.0, &active, bound);
                     active.pop();
-                    #[cfg(feature = "verbose")]
-                    eprint!(".");
+                    score?
                 }
+            };
+            first = Some(score);
+            if !score.is_finite() {
+                continue;
+            }
 
-                f
+            if improves(minimize, score, &best, true) {
+                best = Some((score, f));
             }
-            Self::MinWeighted(Weight::AnswerSetCounting) => {
-                let ub = usize::MAX - 1;
-                let (mut curr, mut f): (usize, Option<(String, SolverLiteral)>) = (0, unsafe {
-                    let s = fs.get_unchecked(0);
-                    Some((
-                        s.to_string(),
-                        *lits
-                            .get(&lex::parse(s).unwrap_unchecked())
-                            .unwrap_unchecked(),
-                    ))
-                });
-
-                if let Some(c) = split_on {
-                    let ub = *c - 1;
-                    for sym in fs {
-                        let l = unsafe {
-                            lits.get(&lex::parse(sym).unwrap_unchecked())
-                                .unwrap_unchecked()
-                        };
-                        let ln = l.negate();
-
-                        active.push(ln);
-                        let count = answer_set_count(nav, &active, curr).ok()?;
-                        if count == ub {
-                            #[cfg(feature = "verbose")]
-                            println!("early stoppage -");
-                            return Some((sym.to_string(), ln));
-                        }
-                        if count >= curr {
-                            curr = count;
-                            f = Some((format!("~{sym}"), ln));
-                        }
-                        active.pop();
+            if optimum == Some(score) {
+                #[cfg(feature = "verbose")]
+                println!("early stoppage");
+                break 'candidates;
+            }
+        }
+        #[cfg(feature = "verbose")]
+        eprint!(".");
+    }
 
-                        let count_ = *c - count;
-                        if count_ == ub {
-                            #[cfg(feature = "verbose")]
-                            println!("early stoppage +");
-                            return Some((sym.to_string(), *l));
-                        }
-                        if count_ >= curr {
-                            curr = count_;
-                            f = Some((sym.to_string(), *l));
-                        }
-                        #[cfg(feature = "verbose")]
-                        eprint!(".");
-                    }
-                } else {
-                    for sym in fs {
-                        let l = unsafe {
-                            lits.get(&lex::parse(sym).unwrap_unchecked())
-                                .unwrap_unchecked()
-                        };
-                        let ln = l.negate();
-
-                        active.push(ln);
-                        let count = answer_set_count(nav, &active, curr).ok()?;
-                        if count == ub {
-                            #[cfg(feature = "verbose")]
-                            println!("early stoppage -");
-                            return Some((sym.to_string(), ln));
-                        }
-                        if count >= curr {
-                            curr = count;
-                            f = Some((format!("~{sym}"), ln));
-                        }
-                        active.pop();
-
-                        active.push(*l);
-                        let count = answer_set_count(nav, &active, curr).ok()?;
-                        if count == ub {
-                            #[cfg(feature = "verbose")]
-                            println!("early stoppage +");
-                            return Some((sym.to_string(), *l));
-                        }
-                        if count >= curr {
-                            curr = count;
-                            f = Some((sym.to_string(), *l));
-                        }
-                        active.pop();
-                        #[cfg(feature = "verbose")]
-                        eprint!(".");
-                    }
-                }
+    if scoring.complements() {
+        *split_on = best.as_ref().map(|(s, _)| *s as usize);
+    }
 
-                *split_on = Some(curr);
+    best.map(|(s, f)| (f, s))
+}
 
-                f
-            }
-            _ => todo!(),
-        };
+/// Returns whether `score` improves upon `best`, where later candidates win `ties` if set.
+#[allow(unused)]
+fn improves(
+    minimize: bool,
+    score: f64,
+    best: &Option<(f64, (String, SolverLiteral))>,
+    ties: bool,
+) -> bool {
+    best.as_ref().is_none_or(|(b, _)| match (minimize, ties) {
+        (true, true) => score <= *b,
+        (true, _) => score < *b,
+        (_, true) => score >= *b,
+        _ => score > *b,
+    })
+}
 
-        #[cfg(feature = "verbose")]
-        eprintln!("\nstep elapsed: {:?}", start.elapsed());
-        ret
+/// Returns facets under `active` in [`Order::Symbol`], which fixes the order candidates are
+/// scored in.
+#[allow(unused)]
+fn current_facets(nav: &mut Navigator, active: &[SolverLiteral]) -> Option<Vec<Symbol>> {
+    let bc = consequences(Consequences::Brave, nav, active)?;
+    match !bc.is_empty() {
+        true => consequences(Consequences::Cautious, nav, active)
+            .map(|cc| Order::Symbol.sorted(bc.difference(&cc))),
+        _ => Some(bc),
+    }
+}
+
+/// Orders facets and their negations by their frequency in a sample of answer sets under `active`,
+/// rarest first if `minimize`, else most frequent first.
+#[allow(unused)]
+fn order_candidates(
+    nav: &mut Navigator,
+    active: &[SolverLiteral],
+    fs: Vec<Symbol>,
+    minimize: bool,
+) -> Vec<(String, SolverLiteral)> {
+    let sample = collect_answer_sets(nav, active, SAMPLE).unwrap_or_default();
+    let n = sample.len().max(1);
+
+    let mut candidates = fs
+        .into_iter()
+        .filter_map(|f| nav.literals.get(&f).map(|l| (f, *l)))
+        .flat_map(|(f, l)| {
+            let c = sample.iter().filter(|xs| xs.contains(&f)).count();
+            [
+                (c, (f.to_string(), l)),
+                (n - c, (format!("~{f}"), l.negate())),
+            ]
+        })
+        .collect::<Vec<_>>();
+    match minimize {
+        true => candidates.sort_by_key(|(c, _)| *c),
+        _ => candidates.sort_by_key(|(c, _)| std::cmp::Reverse(*c)),
     }
+
+    candidates.into_iter().map(|(_, f)| f).collect()
 }
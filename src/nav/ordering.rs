@@ -0,0 +1,39 @@
+use clingo::Symbol;
+
+/// Order of facets and consequences.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Order {
+    /// By string representation, e.g., `a(10)` before `a(2)` before `b`.
+    #[default]
+    Symbol,
+    /// By name, then arity, then string representation.
+    #[allow(unused)]
+    Signature,
+}
+impl Order {
+    /// Sorts `xs` with respect to order.
+    #[allow(unused)]
+    pub fn sort(&self, xs: &mut [Symbol]) {
+        match self {
+            Self::Symbol => xs.sort_by_cached_key(|x| x.to_string()),
+            Self::Signature => xs.sort_by_cached_key(|x| (signature(x), x.to_string())),
+        }
+    }
+
+    /// Returns `xs` sorted with respect to order.
+    #[allow(unused)]
+    pub fn sorted(&self, xs: impl IntoIterator<Item = Symbol>) -> Vec<Symbol> {
+        let mut xs = xs.into_iter().collect::<Vec<_>>();
+        self.sort(&mut xs);
+        xs
+    }
+}
+
+/// Returns name and arity of `x`, where symbols other than functions have empty name and arity 0.
+#[allow(unused)]
+pub fn signature(x: &Symbol) -> (String, usize) {
+    (
+        x.name().map(|n| n.to_owned()).unwrap_or_default(),
+        x.arguments().map(|args| args.len()).unwrap_or(0),
+    )
+}
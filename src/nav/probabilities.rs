@@ -0,0 +1,315 @@
+use super::errors::{NavigatorError, Result};
+use super::faceted_navigation::FacetedNavigation;
+use super::ordering::Order;
+use super::weighted_navigation::{weighted_model_count, Weights};
+use super::{answer_set_count, collect_answer_sets, parse, Essential, Navigator};
+use clingo::SolverLiteral;
+
+use std::collections::HashSet;
+
+#[cfg(feature = "verbose")]
+use std::time::Instant;
+
+/// Returns marginal of every facet under current route in [`Order::Symbol`].
+///
+/// If there are at most `small` answer sets, they are enumerated once and facets are counted in
+/// them; otherwise answer sets are counted once per facet.
+#[allow(unused)]
+pub fn marginals<N: Essential + FacetedNavigation>(
+    nav: &mut N,
+    small: usize,
+) -> Result<Vec<Marginal>> {
+    let Some(fs) = nav.sorted_facets(std::iter::empty::<String>(), Order::Symbol) else {
+        nav.expose().check_stopped()?;
+        return Err(NavigatorError::None);
+    };
+    let mut route = nav.assumptions(std::iter::empty::<String>())?;
+    let nav = nav.expose();
+
+    #[cfg(feature = "verbose")]
+    eprintln!("marginals started");
+    #[cfg(feature = "verbose")]
+    let start = Instant::now();
+    let sample = collect_answer_sets(nav, &route, small.saturating_add(1))?;
+    let marginals = match sample.len() <= small {
+        true => {
+            let sample = sample
+                .into_iter()
+                .map(|xs| xs.into_iter().collect::<HashSet<_>>())
+                .collect::<Vec<_>>();
+            fs.iter()
+                .map(|f| Marginal {
+                    facet: f.to_string(),
+                    count: sample.iter().filter(|xs| xs.contains(f)).count(),
+                    total: sample.len(),
+                })
+                .collect()
+        }
+        _ => {
+            let total = answer_set_count(nav, &route, 0)?;
+            let mut marginals = Vec::with_capacity(fs.len());
+            for f in fs {
+                let Some(l) = nav.literals.get(&f).copied() else {
+                    continue;
+                };
+                route.push(l);
+                let count = answer_set_count(nav, &route, 0)?;
+                route.pop();
+                marginals.push(Marginal {
+                    facet: f.to_string(),
+                    count,
+                    total,
+                });
+            }
+            marginals
+        }
+    };
+
+    #[cfg(feature = "verbose")]
+    eprintln!("marginals elapsed: {:?}", start.elapsed());
+    Ok(marginals)
+}
+
+/// Returns weighted model count under current route and weighted marginal of every facet in
+/// [`Order::Symbol`], i.e., the share of the weighted model count of answer sets containing it.
+#[allow(unused)]
+pub fn weighted_marginals<N: Essential + FacetedNavigation>(
+    nav: &mut N,
+    weights: &Weights,
+) -> Result<WeightedMarginals> {
+    let Some(fs) = nav.sorted_facets(std::iter::empty::<String>(), Order::Symbol) else {
+        nav.expose().check_stopped()?;
+        return Err(NavigatorError::None);
+    };
+    let route = nav.assumptions(std::iter::empty::<String>())?;
+    let nav = nav.expose();
+    let (count, counts) = weighted_model_count(nav, &route, weights, &fs)?;
+
+    Ok(WeightedMarginals {
+        count,
+        marginals: fs
+            .iter()
+            .zip(counts)
+            .map(|(f, c)| match count == 0.0 {
+                true => (f.to_string(), 0.0),
+                _ => (f.to_string(), c / count),
+            })
+            .collect(),
+    })
+}
+
+/// Weighted model count and weighted marginals under current route.
+#[derive(Debug, Clone, PartialEq)]
+pub struct WeightedMarginals {
+    /// Sum of weights of answer sets.
+    pub count: f64,
+    /// Facets and their weighted marginals.
+    pub marginals: Vec<(String, f64)>,
+}
+
+/// Returns P(`query` | route, `given`), i.e., the share of answer sets under current route and
+/// all facets in `given` that also contain all facets in `query`.
+///
+/// Facets are given as `f` or `~f`; unknown facets are rejected.
+#[allow(unused)]
+pub fn conditional<S: ToString>(
+    nav: &mut impl Essential,
+    query: impl Iterator<Item = S>,
+    given: impl Iterator<Item = S>,
+) -> Result<Conditional> {
+    let mut route = nav.assumptions(std::iter::empty::<String>())?;
+    let nav = nav.expose();
+    route.extend(literals(nav, given)?);
+    let query = literals(nav, query)?;
+
+    let denominator = answer_set_count(nav, &route, 0)?;
+    route.extend(query);
+    let numerator = answer_set_count(nav, &route, 0)?;
+
+    Ok(Conditional {
+        numerator,
+        denominator,
+    })
+}
+
+/// Exact counts behind a conditional probability.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Conditional {
+    /// Number of answer sets satisfying query and condition.
+    pub numerator: usize,
+    /// Number of answer sets satisfying condition.
+    pub denominator: usize,
+}
+impl Conditional {
+    /// Returns conditional probability, unless condition is unsatisfiable.
+    #[allow(unused)]
+    pub fn probability(&self) -> Option<f64> {
+        match self.denominator {
+            0 => None,
+            n => Some(self.numerator as f64 / n as f64),
+        }
+    }
+}
+
+/// Returns literals of facets `fs`, each given as `f` or `~f`.
+pub(crate) fn literals<S: ToString>(
+    nav: &Navigator,
+    fs: impl Iterator<Item = S>,
+) -> Result<Vec<SolverLiteral>> {
+    fs.map(|f| {
+        let f = f.to_string();
+        let (symbol, exc) = match f.strip_prefix('~') {
+            Some(symbol) => (symbol, true),
+            _ => (f.as_str(), false),
+        };
+        match parse(symbol).and_then(|sym| nav.literals.get(&sym)) {
+            Some(l) if exc => Ok(l.negate()),
+            Some(l) => Ok(*l),
+            _ => Err(NavigatorError::InvalidInput(f.clone())),
+        }
+    })
+    .collect()
+}
+
+/// Returns co-occurrence counts of every pair of facets under current route, facets in
+/// [`Order::Symbol`].
+///
+/// If there are at most `small` answer sets, they are enumerated once; otherwise answer sets are
+/// counted once per facet and pair of facets.
+#[allow(unused)]
+pub fn co_occurrences<N: Essential + FacetedNavigation>(
+    nav: &mut N,
+    small: usize,
+) -> Result<Vec<CoOccurrence>> {
+    let Some(fs) = nav.sorted_facets(std::iter::empty::<String>(), Order::Symbol) else {
+        nav.expose().check_stopped()?;
+        return Err(NavigatorError::None);
+    };
+    let mut route = nav.assumptions(std::iter::empty::<String>())?;
+    let nav = nav.expose();
+    let fs = fs
+        .into_iter()
+        .filter_map(|f| nav.literals.get(&f).map(|l| (f, *l)))
+        .collect::<Vec<_>>();
+
+    #[cfg(feature = "verbose")]
+    eprintln!("co-occurrences started");
+    #[cfg(feature = "verbose")]
+    let start = Instant::now();
+    let sample = collect_answer_sets(nav, &route, small.saturating_add(1))?;
+    let (total, counts, both) = match sample.len() <= small {
+        true => {
+            let sample = sample
+                .into_iter()
+                .map(|xs| xs.into_iter().collect::<HashSet<_>>())
+                .collect::<Vec<_>>();
+            let count = |f: &[_]| {
+                sample
+                    .iter()
+                    .filter(|xs| f.iter().all(|(f, _)| xs.contains(f)))
+                    .count()
+            };
+            let counts = fs.iter().map(|f| count(&[*f])).collect::<Vec<_>>();
+            let mut both = vec![];
+            for (i, f) in fs.iter().enumerate() {
+                for g in fs[i + 1..].iter() {
+                    both.push(count(&[*f, *g]));
+                }
+            }
+            (sample.len(), counts, both)
+        }
+        _ => {
+            let total = answer_set_count(nav, &route, 0)?;
+            let mut counts = Vec::with_capacity(fs.len());
+            let mut both = vec![];
+            for (i, (_, l)) in fs.iter().enumerate() {
+                route.push(*l);
+                counts.push(answer_set_count(nav, &route, 0)?);
+                for (_, k) in fs[i + 1..].iter() {
+                    route.push(*k);
+                    both.push(answer_set_count(nav, &route, 0)?);
+                    route.pop();
+                }
+                route.pop();
+            }
+            (total, counts, both)
+        }
+    };
+
+    let mut co_occurrences = Vec::with_capacity(both.len());
+    let mut both = both.into_iter();
+    for i in 0..fs.len() {
+        for j in i + 1..fs.len() {
+            let both = both.next().unwrap_or_default();
+            let either = counts[i] + counts[j] - both;
+            co_occurrences.push(CoOccurrence {
+                facets: (fs[i].0.to_string(), fs[j].0.to_string()),
+                counts: (counts[i], counts[j]),
+                both,
+                either,
+                neither: total - either,
+                total,
+            });
+        }
+    }
+
+    #[cfg(feature = "verbose")]
+    eprintln!("co-occurrences elapsed: {:?}", start.elapsed());
+    Ok(co_occurrences)
+}
+
+/// Answer set counts of a pair of facets.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CoOccurrence {
+    pub facets: (String, String),
+    /// Number of answer sets containing each facet.
+    pub counts: (usize, usize),
+    /// Number of answer sets containing both facets.
+    pub both: usize,
+    /// Number of answer sets containing either facet.
+    pub either: usize,
+    /// Number of answer sets containing neither facet.
+    pub neither: usize,
+    /// Number of answer sets under current route.
+    pub total: usize,
+}
+impl CoOccurrence {
+    /// Returns share of answer sets containing both facets among those containing either.
+    #[allow(unused)]
+    pub fn jaccard(&self) -> f64 {
+        match self.either {
+            0 => 0.0,
+            n => self.both as f64 / n as f64,
+        }
+    }
+
+    /// Returns ratio of observed to expected co-occurrence if facets were independent, unless a
+    /// facet is contained in no answer set.
+    #[allow(unused)]
+    pub fn lift(&self) -> Option<f64> {
+        match self.counts.0 * self.counts.1 {
+            0 => None,
+            n => Some((self.both * self.total) as f64 / n as f64),
+        }
+    }
+}
+
+/// Share of answer sets containing a facet.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Marginal {
+    pub facet: String,
+    /// Number of answer sets under current route containing facet.
+    pub count: usize,
+    /// Number of answer sets under current route.
+    pub total: usize,
+}
+impl Marginal {
+    /// Returns fraction of answer sets containing facet.
+    #[allow(unused)]
+    pub fn probability(&self) -> f64 {
+        match self.total {
+            0 => 0.0,
+            n => self.count as f64 / n as f64,
+        }
+    }
+}
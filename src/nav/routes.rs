@@ -0,0 +1,331 @@
+use super::errors::{NavigatorError, Result};
+use super::probabilities::literals;
+use super::{answer_set_count, Essential, FacetRepr, Navigator};
+
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::collections::BTreeSet;
+use std::fmt;
+use std::str::FromStr;
+
+#[cfg(feature = "verbose")]
+use std::time::Instant;
+
+/// Returns minimal route with same answer sets as current route, dropping facets implied by the
+/// remaining ones, first to last. Replaces current route by it if `rewrite` is set.
+///
+/// Only conjunctive routes are supported. Unsatisfiable routes are kept as they are.
+#[allow(unused)]
+pub fn minimize(nav: &mut impl Essential, rewrite: bool) -> Result<Minimized> {
+    let inner = nav.expose();
+    if !inner.disjuncts.is_empty() {
+        return Err(NavigatorError::InvalidInput(inner.route.to_string()));
+    }
+    let facets = inner
+        .conjuncts
+        .1
+        .iter()
+        .map(|f| f.0.clone())
+        .collect::<Vec<_>>();
+
+    #[cfg(feature = "verbose")]
+    eprintln!("route minimization started");
+    #[cfg(feature = "verbose")]
+    let start = Instant::now();
+    // route may be part of the program of current solver, so entailment is checked without it
+    let mut base = Navigator::new(inner.input.0.clone(), inner.input.1.clone())?;
+    base.limit = inner.limit.clone();
+    let ls = literals(&base, facets.iter())?;
+
+    let mut satisfiable = |route: &[_]| {
+        answer_set_count(&mut base, route, 1)
+            .map(|n| n > 0)
+            .inspect_err(|_| inner.stopped = base.stopped.take())
+    };
+    let mut kept = (0..facets.len()).collect::<Vec<_>>();
+    // an unsatisfiable route implies each of its facets
+    if satisfiable(&ls)? {
+        for i in 0..facets.len() {
+            let mut route = kept
+                .iter()
+                .filter(|j| **j != i)
+                .map(|j| ls[*j])
+                .collect::<Vec<_>>();
+            route.push(ls[i].negate());
+            if !satisfiable(&route)? {
+                kept.retain(|j| *j != i);
+            }
+        }
+    }
+    #[cfg(feature = "verbose")]
+    eprintln!("route minimization elapsed: {:?}", start.elapsed());
+
+    let minimized = Minimized {
+        route: kept.iter().map(|i| facets[*i].clone()).collect(),
+        redundant: (0..facets.len())
+            .filter(|i| !kept.contains(i))
+            .map(|i| facets[i].clone())
+            .collect(),
+    };
+    if rewrite {
+        inner.conjuncts = (
+            kept.iter().map(|i| inner.conjuncts.0[*i]).collect(),
+            kept.iter().map(|i| FacetRepr(facets[*i].clone())).collect(),
+        );
+        inner.route = Route::default();
+        for i in kept.iter() {
+            inner.route.push(Connective::And, facets[*i].parse()?);
+        }
+        nav.update()?;
+    }
+
+    Ok(minimized)
+}
+
+/// Minimal route and facets dropped from current route.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Minimized {
+    /// Facets of minimal route.
+    pub route: Vec<String>,
+    /// Facets implied by minimal route.
+    pub redundant: Vec<String>,
+}
+
+/// Returns canonical form of current route.
+#[allow(unused)]
+pub fn normalize(nav: &impl Essential) -> Normal {
+    Normal::from(&nav.route())
+}
+
+/// Connective a facet is activated with.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Connective {
+    And,
+    Or,
+}
+
+/// Facet `f` or its negation `~f`.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Facet {
+    pub atom: String,
+    pub negated: bool,
+}
+impl fmt::Display for Facet {
+    /// Writes `~f`, or `¬f` in alternate form.
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match (self.negated, f.alternate()) {
+            (true, true) => write!(f, "¬{}", self.atom),
+            (true, _) => write!(f, "~{}", self.atom),
+            _ => write!(f, "{}", self.atom),
+        }
+    }
+}
+impl FromStr for Facet {
+    type Err = NavigatorError;
+
+    /// Parses `f`, `~f` or `¬f`.
+    fn from_str(s: &str) -> Result<Self> {
+        let (atom, negated) = match s.strip_prefix('~').or_else(|| s.strip_prefix('¬')) {
+            Some(atom) => (atom, true),
+            _ => (s, false),
+        };
+        match atom.is_empty() || atom.contains(char::is_whitespace) {
+            true => Err(NavigatorError::InvalidInput(s.to_owned())),
+            _ => Ok(Self {
+                atom: atom.to_owned(),
+                negated,
+            }),
+        }
+    }
+}
+
+/// Sequence of facets, each activated conjunctively or disjunctively.
+///
+/// Written as `& a & ~c | d`, or `∧ a ∧ ¬c ∨ d` in alternate form, and serialized as such.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+pub struct Route(Vec<(Connective, Facet)>);
+impl Route {
+    #[allow(unused)]
+    pub fn push(&mut self, connective: Connective, facet: Facet) {
+        self.0.push((connective, facet))
+    }
+
+    #[allow(unused)]
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    #[allow(unused)]
+    pub fn iter(&self) -> impl Iterator<Item = &(Connective, Facet)> {
+        self.0.iter()
+    }
+
+    /// Returns facets regardless of connective, e.g., to peek on.
+    #[allow(unused)]
+    pub fn facets(&self) -> impl Iterator<Item = &Facet> {
+        self.0.iter().map(|(_, f)| f)
+    }
+
+    #[allow(unused)]
+    pub fn conjuncts(&self) -> impl Iterator<Item = &Facet> {
+        self.connected(Connective::And)
+    }
+
+    #[allow(unused)]
+    pub fn disjuncts(&self) -> impl Iterator<Item = &Facet> {
+        self.connected(Connective::Or)
+    }
+
+    fn connected(&self, connective: Connective) -> impl Iterator<Item = &Facet> {
+        self.0
+            .iter()
+            .filter(move |(c, _)| *c == connective)
+            .map(|(_, f)| f)
+    }
+}
+impl fmt::Display for Route {
+    /// Writes ASCII connectives, or Unicode connectives in alternate form.
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let unicode = f.alternate();
+        for (i, (c, facet)) in self.0.iter().enumerate() {
+            let c = match (c, unicode) {
+                (Connective::And, true) => "∧",
+                (Connective::And, _) => "&",
+                (Connective::Or, true) => "∨",
+                (Connective::Or, _) => "|",
+            };
+            if i > 0 {
+                write!(f, " ")?;
+            }
+            match unicode {
+                true => write!(f, "{c} {facet:#}")?,
+                _ => write!(f, "{c} {facet}")?,
+            }
+        }
+        Ok(())
+    }
+}
+impl FromStr for Route {
+    type Err = NavigatorError;
+
+    /// Parses facets separated by connectives in either notation, where a leading facet without
+    /// connective is activated conjunctively.
+    fn from_str(s: &str) -> Result<Self> {
+        let mut route = Self::default();
+        let mut tokens = s.split_whitespace().peekable();
+        if tokens.peek().is_some_and(|t| !is_connective(t)) {
+            let facet = tokens.next().unwrap_or_default().parse()?;
+            route.push(Connective::And, facet);
+        }
+        while let Some(token) = tokens.next() {
+            let connective = match token {
+                "&" | "∧" => Connective::And,
+                "|" | "∨" => Connective::Or,
+                _ => return Err(NavigatorError::InvalidInput(token.to_owned())),
+            };
+            let facet = tokens
+                .next()
+                .ok_or(NavigatorError::InvalidInput(s.to_owned()))?
+                .parse()?;
+            route.push(connective, facet);
+        }
+        Ok(route)
+    }
+}
+impl Serialize for Route {
+    fn serialize<S: Serializer>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+impl<'de> Deserialize<'de> for Route {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> std::result::Result<Self, D::Error> {
+        String::deserialize(deserializer)?
+            .parse()
+            .map_err(serde::de::Error::custom)
+    }
+}
+
+fn is_connective(token: &str) -> bool {
+    matches!(token, "&" | "∧" | "|" | "∨")
+}
+
+/// Canonical form of a route, i.e., its conjunctive and disjunctive facets deduplicated and
+/// sorted by atom, positive before negated.
+///
+/// Contradictions and tautologies are detected syntactically, i.e., `f & ~f` and `| f | ~f`.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Normal {
+    pub conjuncts: Vec<String>,
+    pub disjuncts: Vec<String>,
+}
+impl Normal {
+    /// Parses route as built by activating facets, e.g., ` & a & ~c | d`, ignoring invalid parts.
+    #[allow(unused)]
+    pub fn parse(route: &str) -> Self {
+        let mut conjuncts = BTreeSet::new();
+        let mut disjuncts = BTreeSet::new();
+        let mut tokens = route.split_whitespace();
+        while let (Some(token), Some(f)) = (tokens.next(), tokens.next()) {
+            let key = signed(f);
+            match token {
+                "&" => conjuncts.insert(key),
+                "|" => disjuncts.insert(key),
+                _ => false,
+            };
+        }
+        let unsign = |fs: BTreeSet<(String, bool)>| {
+            fs.into_iter()
+                .map(|(f, exc)| match exc {
+                    true => format!("~{f}"),
+                    _ => f,
+                })
+                .collect()
+        };
+
+        Self {
+            conjuncts: unsign(conjuncts),
+            disjuncts: unsign(disjuncts),
+        }
+    }
+
+    /// Returns whether conjunctive part activates a facet and its negation.
+    #[allow(unused)]
+    pub fn is_contradictory(&self) -> bool {
+        complementary(&self.conjuncts)
+    }
+
+    /// Returns whether disjunctive part activates a facet and its negation.
+    #[allow(unused)]
+    pub fn is_tautological(&self) -> bool {
+        complementary(&self.disjuncts)
+    }
+}
+impl From<&Route> for Normal {
+    fn from(route: &Route) -> Self {
+        Self::parse(&route.to_string())
+    }
+}
+impl fmt::Display for Normal {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for c in self.conjuncts.iter() {
+            write!(f, " & {c}")?;
+        }
+        for d in self.disjuncts.iter() {
+            write!(f, " | {d}")?;
+        }
+        Ok(())
+    }
+}
+
+/// Returns atom of facet `f` and whether it is negated.
+fn signed(f: &str) -> (String, bool) {
+    match f.strip_prefix('~') {
+        Some(atom) => (atom.to_owned(), true),
+        _ => (f.to_owned(), false),
+    }
+}
+
+/// Returns whether sorted facets `fs` contain a facet and its negation.
+fn complementary(fs: &[String]) -> bool {
+    fs.windows(2)
+        .any(|w| w[1].strip_prefix('~') == Some(w[0].as_str()))
+}
@@ -1,8 +1,11 @@
+use super::errors::{NavigatorError, Result};
 use super::{
-    answer_set_count,
+    answer_set_count, collect_answer_sets,
     faceted_navigation::{fs_stats, FacetedNavigation},
-    Essential,
+    parse, Essential, Navigator,
 };
+use clingo::{SolverLiteral, Symbol};
+use std::collections::{HashMap, HashSet};
 
 #[cfg(feature = "verbose")]
 use std::time::Instant;
@@ -16,6 +19,16 @@ pub fn count<S: ToString, T>(
     sharp.eval_sharp(nav, peek_on)
 }
 
+/// Returns weight of current route extended by `peek_on`, also if it is no integer.
+#[allow(unused)]
+pub fn weigh<S: ToString, T>(
+    sharp: &mut impl WeightedNavigation<T>,
+    nav: &mut T,
+    peek_on: impl Iterator<Item = S>,
+) -> Option<f64> {
+    sharp.eval_weighted(nav, peek_on)
+}
+
 pub trait WeightedNavigation<T> {
     fn eval_sharp<S: ToString>(
         &mut self,
@@ -28,6 +41,12 @@ pub trait WeightedNavigation<T> {
         peek_on: impl Iterator<Item = S>,
         target: &[S],
     ) -> Option<usize>;
+    /// Returns weight as real number, supported by all weights unlike [`Self::eval_sharp`].
+    fn eval_weighted<S: ToString>(
+        &mut self,
+        nav: &mut T,
+        peek_on: impl Iterator<Item = S>,
+    ) -> Option<f64>;
 }
 
 #[derive(Debug, Clone)]
@@ -38,6 +57,101 @@ pub enum Weight {
     BcCounting,
     #[allow(unused)]
     CcCounting,
+    /// Sums weights of answer sets. Weighted counts are no integers, so they are only evaluated
+    /// by [`WeightedNavigation::eval_weighted`] and `eval_sharp` returns `None`.
+    #[allow(unused)]
+    WeightedModelCounting(Weights),
+}
+
+/// Weights of atoms, where the weight of an answer set is the product of the weights of all
+/// annotated atoms, depending on whether they are contained. Annotated atoms must be shown.
+#[derive(Debug, Clone, Default)]
+pub struct Weights(HashMap<Symbol, (f64, f64)>);
+impl Weights {
+    /// Annotates each atom with its weight if contained and its weight if not contained.
+    #[allow(unused)]
+    pub fn new<S: ToString>(weights: impl Iterator<Item = (S, f64, f64)>) -> Result<Self> {
+        weights
+            .map(|(atom, w, nw)| {
+                let atom = atom.to_string();
+                parse(&atom)
+                    .map(|sym| (sym, (w, nw)))
+                    .ok_or(NavigatorError::InvalidInput(atom))
+            })
+            .collect::<Result<HashMap<_, _>>>()
+            .map(Self)
+    }
+
+    /// Annotates each atom with an independent probability `p`, i.e., ProbLog-style weights `p`
+    /// if contained and `1 - p` if not.
+    #[allow(unused)]
+    pub fn probabilities<S: ToString>(
+        probabilities: impl Iterator<Item = (S, f64)>,
+    ) -> Result<Self> {
+        Self::new(probabilities.map(|(atom, p)| (atom, p, 1.0 - p)))
+    }
+
+    /// Annotates `atoms` via `w`, called with each atom and whether it is contained.
+    #[allow(unused)]
+    pub fn from_fn<S: ToString>(
+        atoms: impl Iterator<Item = S>,
+        w: impl Fn(&Symbol, bool) -> f64,
+    ) -> Result<Self> {
+        let atoms = atoms
+            .map(|atom| {
+                let atom = atom.to_string();
+                parse(&atom).ok_or(NavigatorError::InvalidInput(atom))
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        Ok(Self(
+            atoms
+                .into_iter()
+                .map(|sym| (sym, (w(&sym, true), w(&sym, false))))
+                .collect(),
+        ))
+    }
+
+    /// Returns weight of `answer_set`.
+    #[allow(unused)]
+    pub fn weight(&self, answer_set: &HashSet<Symbol>) -> f64 {
+        self.0
+            .iter()
+            .map(|(sym, (w, nw))| match answer_set.contains(sym) {
+                true => w,
+                _ => nw,
+            })
+            .product()
+    }
+}
+
+/// Returns weighted model count under `route` and weighted count of answer sets containing each
+/// of `fs`.
+pub(crate) fn weighted_model_count(
+    nav: &mut Navigator,
+    route: &[SolverLiteral],
+    weights: &Weights,
+    fs: &[Symbol],
+) -> Result<(f64, Vec<f64>)> {
+    #[cfg(feature = "verbose")]
+    eprintln!("weighted model counting started");
+    #[cfg(feature = "verbose")]
+    let start = Instant::now();
+    let mut total = 0.0;
+    let mut counts = vec![0.0; fs.len()];
+    for xs in collect_answer_sets(nav, route, 0)? {
+        let xs = xs.into_iter().collect::<HashSet<_>>();
+        let w = weights.weight(&xs);
+        total += w;
+        fs.iter()
+            .zip(counts.iter_mut())
+            .filter(|(f, _)| xs.contains(f))
+            .for_each(|(_, c)| *c += w);
+    }
+
+    #[cfg(feature = "verbose")]
+    eprintln!("weighted model counting elapsed: {:?}", start.elapsed());
+    Ok((total, counts))
 }
 
 impl<T: FacetedNavigation + Essential> WeightedNavigation<T> for Weight {
@@ -70,6 +184,7 @@ impl<T: FacetedNavigation + Essential> WeightedNavigation<T> for Weight {
             }
             Self::BcCounting => fs_stats(nav, peek_on).and_then(|(bcc, _, _)| Some(bcc)),
             Self::CcCounting => fs_stats(nav, peek_on).and_then(|(_, ccc, _)| Some(ccc)),
+            Self::WeightedModelCounting(_) => None,
         }
     }
     fn eval_sharp_restricted<S: ToString>(
@@ -80,4 +195,19 @@ impl<T: FacetedNavigation + Essential> WeightedNavigation<T> for Weight {
     ) -> Option<usize> {
         todo!()
     }
+    fn eval_weighted<S: ToString>(
+        &mut self,
+        nav: &mut T,
+        peek_on: impl Iterator<Item = S>,
+    ) -> Option<f64> {
+        match self {
+            Self::WeightedModelCounting(weights) => {
+                let route = nav.assumptions(peek_on).ok()?;
+                weighted_model_count(nav.expose(), &route, weights, &[])
+                    .ok()
+                    .map(|(wmc, _)| wmc)
+            }
+            _ => self.eval_sharp(nav, peek_on).map(|c| c as f64),
+        }
+    }
 }